@@ -0,0 +1,96 @@
+//! Service configuration, loaded once from the environment
+//! (see `.cargo/config.toml` for the dev values)
+
+use crate::log::RequestLogKind;
+use crate::model::StoreKind;
+use crate::web::problem::ErrorFormat;
+use crate::{Error, Result};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use std::{env, str::FromStr, sync::OnceLock};
+
+pub fn config() -> &'static Config {
+    static INSTANCE: OnceLock<Config> = OnceLock::new();
+
+    INSTANCE.get_or_init(|| {
+        Config::load_from_env()
+            .unwrap_or_else(|ex| panic!("FATAL - WHILE LOADING CONF - Cause: {ex:?}"))
+    })
+}
+
+pub struct Config {
+    // -- Crypt
+    pub token_key: Vec<u8>,
+    pub token_duration_sec: u64,
+    pub token_renew_window_sec: u64,
+    pub session_max_sec: u64,
+
+    // -- Model
+    pub store: StoreKind,
+    pub db_path: String, // sqlite only, `:memory:` for an in memory database
+    pub ticket_retention_sec: u64, // soft deleted tickets are purged after
+
+    // -- Web
+    pub error_format: ErrorFormat, // unless the request `Accept` selects one
+
+    // -- Log
+    pub request_log: RequestLogKind,
+    pub request_log_path: String, // file only, rotated next to it
+    pub request_log_max_bytes: u64,
+    pub request_log_rotate_sec: u64,
+    pub request_log_keep_files: usize,
+    pub request_log_recent: usize, // lines kept in memory, for the admin API
+
+    // -- Seed
+    pub admin_username: String,
+    pub admin_pwd: String,
+}
+
+impl Config {
+    fn load_from_env() -> Result<Config> {
+        Ok(Config {
+            // -- Crypt
+            token_key: get_env_b64u_as_u8s("SERVICE_TOKEN_KEY")?,
+            token_duration_sec: get_env_parse("SERVICE_TOKEN_DURATION_SEC")?,
+            token_renew_window_sec: get_env_parse("SERVICE_TOKEN_RENEW_WINDOW_SEC")?,
+            session_max_sec: get_env_parse("SERVICE_SESSION_MAX_SEC")?,
+
+            // -- Model
+            store: get_env_parse("SERVICE_STORE")?,
+            db_path: get_env("SERVICE_DB_PATH")?,
+            ticket_retention_sec: get_env_parse("SERVICE_TICKET_RETENTION_SEC")?,
+
+            // -- Web
+            error_format: get_env_parse("SERVICE_ERROR_FORMAT")?,
+
+            // -- Log
+            request_log: get_env_parse("SERVICE_REQUEST_LOG")?,
+            request_log_path: get_env("SERVICE_REQUEST_LOG_PATH")?,
+            request_log_max_bytes: get_env_parse("SERVICE_REQUEST_LOG_MAX_BYTES")?,
+            request_log_rotate_sec: get_env_parse("SERVICE_REQUEST_LOG_ROTATE_SEC")?,
+            request_log_keep_files: get_env_parse("SERVICE_REQUEST_LOG_KEEP_FILES")?,
+            request_log_recent: get_env_parse("SERVICE_REQUEST_LOG_RECENT")?,
+
+            // -- Seed
+            admin_username: get_env("SERVICE_ADMIN_USERNAME")?,
+            admin_pwd: get_env("SERVICE_ADMIN_PWD")?,
+        })
+    }
+}
+
+// region: --- Env Helpers
+fn get_env(name: &'static str) -> Result<String> {
+    env::var(name).map_err(|_| Error::ConfigMissingEnv(name))
+}
+
+fn get_env_parse<T: FromStr>(name: &'static str) -> Result<T> {
+    get_env(name)?
+        .parse::<T>()
+        .map_err(|_| Error::ConfigWrongFormat(name))
+}
+
+fn get_env_b64u_as_u8s(name: &'static str) -> Result<Vec<u8>> {
+    URL_SAFE_NO_PAD
+        .decode(get_env(name)?)
+        .map_err(|_| Error::ConfigWrongFormat(name))
+}
+// endregion: --- Env Helpers
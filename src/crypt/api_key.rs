@@ -0,0 +1,69 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use lazy_regex::regex_captures;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::crypt::{EncryptContent, encrypt_into_b64u, verify_b64u};
+use crate::{Error, Result};
+
+// region: --- ApiKeyToken Type
+/// String format: `key-[id].[secret]` (secret in base64url)
+/// Only the HMAC of the secret is stored, so the key is shown once at creation.
+pub struct ApiKeyToken {
+    pub id: Uuid,
+    pub secret_b64u: String,
+}
+
+impl FromStr for ApiKeyToken {
+    type Err = Error;
+
+    fn from_str(key_str: &str) -> Result<Self> {
+        let (_whole, id, secret_b64u) =
+            regex_captures!(r#"^key-([0-9a-f-]{36})\.([A-Za-z0-9_-]+)$"#, key_str)
+                .ok_or(Error::AuthFailTokenWrongFormat)?;
+
+        Ok(Self {
+            id: id.parse().map_err(|_| Error::AuthFailTokenWrongFormat)?,
+            secret_b64u: secret_b64u.to_string(),
+        })
+    }
+}
+
+impl Display for ApiKeyToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "key-{}.{}", self.id, self.secret_b64u)
+    }
+}
+// endregion: --- ApiKeyToken Type
+
+// region: --- ApiKey Gen and Validation
+/// Generate a new key, and the hash to store.
+pub fn generate_api_key() -> Result<(ApiKeyToken, String)> {
+    let mut secret = [0u8; 32];
+    OsRng.fill_bytes(&mut secret);
+
+    let key = ApiKeyToken {
+        id: Uuid::new_v4(),
+        secret_b64u: URL_SAFE_NO_PAD.encode(secret),
+    };
+    let key_hash = encrypt_into_b64u(&config().token_key, &key_content(&key))?;
+
+    Ok((key, key_hash))
+}
+
+pub fn validate_api_key(key: &ApiKeyToken, key_hash: &str) -> Result<()> {
+    verify_b64u(&config().token_key, &key_content(key), key_hash)
+        .map_err(|_| Error::AuthFailApiKeyInvalid)
+}
+
+fn key_content(key: &ApiKeyToken) -> EncryptContent {
+    EncryptContent {
+        content: key.secret_b64u.clone(),
+        salt: key.id.to_string(),
+    }
+}
+// endregion: --- ApiKey Gen and Validation
@@ -0,0 +1,45 @@
+//! Crypt Layer
+//! (auth token and API key signatures, password hashing)
+
+pub mod api_key;
+pub mod pwd;
+pub mod token;
+
+use crate::{Error, Result};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+
+pub struct EncryptContent {
+    pub content: String, // Clear content
+    pub salt: String,    // Clear salt
+}
+
+/// Sign the content + salt with HMAC-SHA512 and return the base64url signature.
+pub fn encrypt_into_b64u(key: &[u8], enc_content: &EncryptContent) -> Result<String> {
+    let hmac = new_hmac(key, enc_content)?;
+    let result = hmac.finalize().into_bytes();
+
+    Ok(URL_SAFE_NO_PAD.encode(result))
+}
+
+/// Verify a base64url signature against the content + salt (constant-time).
+pub fn verify_b64u(key: &[u8], enc_content: &EncryptContent, sign_b64u: &str) -> Result<()> {
+    let sign = URL_SAFE_NO_PAD
+        .decode(sign_b64u)
+        .map_err(|_| Error::AuthFailTokenBadSignature)?;
+
+    new_hmac(key, enc_content)?
+        .verify_slice(&sign)
+        .map_err(|_| Error::AuthFailTokenBadSignature)
+}
+
+fn new_hmac(key: &[u8], enc_content: &EncryptContent) -> Result<Hmac<Sha512>> {
+    let EncryptContent { content, salt } = enc_content;
+
+    let mut hmac = Hmac::<Sha512>::new_from_slice(key).map_err(|_| Error::CryptKeyFailHmac)?;
+    hmac.update(content.as_bytes());
+    hmac.update(salt.as_bytes());
+
+    Ok(hmac)
+}
@@ -0,0 +1,34 @@
+//! Password hashing with Argon2id (memory-hard).
+//! The PHC string output embeds the per-user random salt and the parameters.
+
+use argon2::password_hash::{PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng};
+use argon2::{Argon2, PasswordHash};
+
+use crate::{Error, Result};
+
+/// Hash the clear password into a PHC string (e.g., `$argon2id$v=19$...`).
+pub async fn hash_pwd(pwd_clear: String) -> Result<String> {
+    // CPU (and memory) intensive, so off the async runtime threads
+    tokio::task::spawn_blocking(move || {
+        let salt = SaltString::generate(&mut OsRng);
+
+        Argon2::default()
+            .hash_password(pwd_clear.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|_| Error::CryptPwdHashFail)
+    })
+    .await
+    .map_err(|_| Error::CryptPwdHashFail)?
+}
+
+pub async fn validate_pwd(pwd_clear: String, pwd_hash: String) -> Result<()> {
+    tokio::task::spawn_blocking(move || {
+        let pwd_hash = PasswordHash::new(&pwd_hash).map_err(|_| Error::CryptPwdHashFail)?;
+
+        Argon2::default()
+            .verify_password(pwd_clear.as_bytes(), &pwd_hash)
+            .map_err(|_| Error::CryptPwdNotMatching)
+    })
+    .await
+    .map_err(|_| Error::CryptPwdHashFail)?
+}
@@ -0,0 +1,120 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use lazy_regex::regex_captures;
+
+use crate::config::config;
+use crate::crypt::{EncryptContent, encrypt_into_b64u, verify_b64u};
+use crate::utils::now_utc_sec;
+use crate::{Error, Result};
+
+// region: --- Token Type
+/// String format: `user-[user_id].[origin].[expiration].[signature]`
+/// (origin is the session start, both in seconds since UNIX epoch,
+/// signature in base64url)
+#[derive(Debug)]
+pub struct Token {
+    pub user_id: u64,
+    pub origin: u64,
+    pub exp: u64,
+    pub sign_b64u: String,
+}
+
+impl FromStr for Token {
+    type Err = Error;
+
+    fn from_str(token_str: &str) -> Result<Self> {
+        let (_whole, user_id, origin, exp, sign_b64u) =
+            regex_captures!(r#"^user-(\d+)\.(\d+)\.(\d+)\.([A-Za-z0-9_-]+)$"#, token_str)
+                .ok_or(Error::AuthFailTokenWrongFormat)?;
+
+        Ok(Self {
+            user_id: user_id
+                .parse()
+                .map_err(|_| Error::AuthFailTokenWrongFormat)?,
+            origin: origin
+                .parse()
+                .map_err(|_| Error::AuthFailTokenWrongFormat)?,
+            exp: exp.parse().map_err(|_| Error::AuthFailTokenWrongFormat)?,
+            sign_b64u: sign_b64u.to_string(),
+        })
+    }
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "user-{}.{}.{}.{}",
+            self.user_id, self.origin, self.exp, self.sign_b64u
+        )
+    }
+}
+// endregion: --- Token Type
+
+// region: --- Web Token Gen and Validation
+/// Generate the token of a new session (i.e., at login).
+pub fn generate_web_token(user_id: u64, salt: &str) -> Result<Token> {
+    _generate_web_token(user_id, now_utc_sec(), salt)
+}
+
+/// Generate a fresh token for the same session, keeping its origin.
+/// The expiration is capped by the session max lifetime.
+pub fn renew_web_token(token: &Token, salt: &str) -> Result<Token> {
+    _generate_web_token(token.user_id, token.origin, salt)
+}
+
+/// Whether a (valid) token expires within the renewal window,
+/// and can still be extended within the session max lifetime.
+pub fn should_renew_web_token(token: &Token) -> bool {
+    let config = config();
+
+    token.exp <= now_utc_sec() + config.token_renew_window_sec
+        && token.exp < token.origin + config.session_max_sec
+}
+
+/// Check the signature first, then the expiration and the session max lifetime.
+pub fn validate_web_token(token: &Token, salt: &str) -> Result<()> {
+    let config = config();
+
+    verify_b64u(
+        &config.token_key,
+        &token_content(token.user_id, token.origin, token.exp, salt),
+        &token.sign_b64u,
+    )?;
+
+    let now = now_utc_sec();
+    if token.origin + config.session_max_sec <= now {
+        return Err(Error::AuthFailSessionMaxLifetime);
+    }
+    if token.exp <= now {
+        return Err(Error::AuthFailExpired);
+    }
+
+    Ok(())
+}
+
+fn _generate_web_token(user_id: u64, origin: u64, salt: &str) -> Result<Token> {
+    let config = config();
+    let exp = (now_utc_sec() + config.token_duration_sec).min(origin + config.session_max_sec);
+
+    let sign_b64u = encrypt_into_b64u(
+        &config.token_key,
+        &token_content(user_id, origin, exp, salt),
+    )?;
+
+    Ok(Token {
+        user_id,
+        origin,
+        exp,
+        sign_b64u,
+    })
+}
+
+fn token_content(user_id: u64, origin: u64, exp: u64, salt: &str) -> EncryptContent {
+    EncryptContent {
+        content: format!("{user_id}.{origin}.{exp}"),
+        salt: salt.to_string(),
+    }
+}
+// endregion: --- Web Token Gen and Validation
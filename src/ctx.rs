@@ -1,12 +1,20 @@
+use serde::{Deserialize, Serialize};
+
 #[derive(Clone, Debug)]
 pub struct Ctx {
     user_id: u64,
+    roles: Vec<Role>,
+    scopes: Option<Vec<Permission>>, // e.g., API key scopes, None for all
 }
 
 // Constructor
 impl Ctx {
-    pub fn new(user_id: u64) -> Self {
-        Self { user_id }
+    pub fn new(user_id: u64, roles: Vec<Role>, scopes: Option<Vec<Permission>>) -> Self {
+        Self {
+            user_id,
+            roles,
+            scopes,
+        }
     }
 }
 
@@ -15,4 +23,79 @@ impl Ctx {
     pub fn user_id(&self) -> u64 {
         self.user_id
     }
+
+    pub fn scopes(&self) -> Option<&[Permission]> {
+        self.scopes.as_deref()
+    }
+}
+
+// Access checks
+impl Ctx {
+    /// A permission is granted when any of the user roles grants it,
+    /// and it is within the scopes (if any).
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.in_scopes(permission)
+            && self
+                .roles
+                .iter()
+                .any(|role| role.permissions().contains(&permission))
+    }
+
+    /// Whether the scopes (if any) allow the permission, whatever the roles
+    /// (e.g., for `CredentialManage`, which every user has).
+    pub fn in_scopes(&self, permission: Permission) -> bool {
+        self.scopes
+            .as_ref()
+            .is_none_or(|scopes| scopes.contains(&permission))
+    }
+}
+
+// region: --- Roles & Permissions
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Admin,
+    Agent,
+    Reporter,
+}
+
+/// Permissions beyond the user own resources
+/// (e.g., any user can delete its own tickets, only `TicketDeleteAny` can delete others'),
+/// except the ones on the own resources (e.g., `TicketDeleteOwn`), which every user has,
+/// and are only there to be scoped out (e.g., a read only API key).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Permission {
+    TicketReadAny,
+    TicketUpdateAny,
+    TicketDeleteAny,
+    TicketPurge, // hard delete of soft deleted tickets
+    UserManage,
+    RequestLogRead, // all users' request logs
+
+    // -- On the own resources, unless scoped out
+    TicketCreate,
+    TicketUpdateOwn,
+    TicketDeleteOwn,  // also restore
+    CommentWrite,     // create, edit and delete the own comments
+    CredentialManage, // API keys and sessions (e.g., log out all)
+}
+
+impl Role {
+    pub fn permissions(&self) -> &'static [Permission] {
+        use Permission::*;
+
+        match self {
+            Role::Admin => &[
+                TicketReadAny,
+                TicketUpdateAny,
+                TicketDeleteAny,
+                TicketPurge,
+                UserManage,
+                RequestLogRead,
+            ],
+            Role::Agent => &[TicketReadAny, TicketUpdateAny],
+            Role::Reporter => &[],
+        }
+    }
 }
+// endregion: --- Roles & Permissions
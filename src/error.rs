@@ -1,8 +1,16 @@
+use std::borrow::Cow;
+
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
 use serde::Serialize;
+use serde_json::{Value, json};
+use tracing::{debug, error};
+use uuid::Uuid;
+
+use crate::ctx::Permission;
+use crate::model::TicketStatus;
 
 pub type Result<T> = core::result::Result<T, Error>;
 
@@ -10,20 +18,120 @@ pub type Result<T> = core::result::Result<T, Error>;
 #[derive(Clone, Debug, Serialize, strum_macros::AsRefStr)]
 #[serde(tag = "type", content = "data")]
 pub enum Error {
-    LoginFail,
+    LoginFailUsernameNotFound,
+    LoginFailPwdNotMatching {
+        user_id: u64,
+    },
+
+    RegisterFailValidation {
+        errors: Vec<FieldError>,
+    },
+
+    // -- Request body, query and path errors
+    BodyFailJson {
+        errors: Vec<FieldError>,
+    },
+    BodyFailValidation {
+        errors: Vec<FieldError>,
+    },
+    QueryFailParams {
+        errors: Vec<FieldError>,
+    },
+    PathFailParams {
+        errors: Vec<FieldError>,
+    },
+
+    // -- Config errors
+    ConfigMissingEnv(&'static str),
+    ConfigWrongFormat(&'static str),
+
+    // -- Crypt errors
+    CryptKeyFailHmac,
+    CryptPwdHashFail,
+    CryptPwdNotMatching,
 
     // -- Auth errors
     AuthFailNoAuthTokenCookie,
     AuthFailTokenWrongFormat,
+    AuthFailTokenBadSignature,
+    AuthFailExpired,
+    AuthFailSessionMaxLifetime,
+    AuthFailTokenRevoked,
+    AuthFailApiKeyInvalid,
+    AuthFailApiKeyExpired,
+    AuthFailUnknownUser {
+        user_id: u64,
+    },
     AuthFailCtxNotInRequestExt,
+    ReqStampNotInReqExt,
+    AuthFailNoPermission {
+        permission: Permission,
+    },
 
     // -- Model errors, refactor in model layer
-    TicketDeleteFailIdNotFound { id: u64 },
+    TicketNotFound {
+        id: u64,
+    },
+    TicketAccessDenied {
+        id: u64,
+    },
+    TicketDeleted {
+        id: u64,
+    },
+    TicketNotDeleted {
+        id: u64,
+    },
+    TicketVersionConflict {
+        id: u64,
+        expected: Vec<u64>, // any of them
+        actual: u64,
+    },
+    TicketFailValidation {
+        errors: Vec<FieldError>,
+    },
+    TicketListCursorInvalid,
+    InvalidTransition {
+        from: TicketStatus,
+        to: TicketStatus,
+    },
+    CommentNotFound {
+        id: u64,
+    },
+    CommentAccessDenied {
+        id: u64,
+    },
+    UserNotFound {
+        id: u64,
+    },
+    UserUsernameAlreadyExists {
+        username: String,
+    },
+    ApiKeyNotFound {
+        id: Uuid,
+    },
+
+    // -- Store errors
+    StoreFail(String),
+
+    // -- Log errors
+    RequestLogFail(String),
+}
+
+/// A client input error, on a given field.
+#[derive(Clone, Debug, Serialize)]
+pub struct FieldError {
+    pub field: Cow<'static, str>, // e.g., `title`, or `items[2].name` for nested ones
+    pub message: String,
 }
 
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
-        println!("->> {:<12} - {self:?}", "INTO_RES");
+        // Client errors are expected, only the service ones are worth an error
+        if self.client_status_and_error().0.is_server_error() {
+            error!("{:<12} - {self:?}", "INTO_RES");
+        } else {
+            debug!("{:<12} - {self:?}", "INTO_RES");
+        }
 
         // Create a placeholder Axum response
         let mut response = StatusCode::INTERNAL_SERVER_ERROR.into_response();
@@ -49,17 +157,54 @@ impl Error {
     pub fn client_status_and_error(&self) -> (StatusCode, ClientError) {
         #[allow(unreachable_patterns)]
         match self {
-            Self::LoginFail => (StatusCode::FORBIDDEN, ClientError::LOGIN_FAIL),
+            Self::LoginFailUsernameNotFound | Self::LoginFailPwdNotMatching { .. } => {
+                (StatusCode::FORBIDDEN, ClientError::LOGIN_FAIL)
+            }
+
+            Self::RegisterFailValidation { .. }
+            | Self::BodyFailJson { .. }
+            | Self::BodyFailValidation { .. }
+            | Self::QueryFailParams { .. }
+            | Self::PathFailParams { .. } => (StatusCode::BAD_REQUEST, ClientError::INVALID_PARAMS),
 
             // -- Auth
             Self::AuthFailNoAuthTokenCookie
             | Self::AuthFailTokenWrongFormat
+            | Self::AuthFailTokenBadSignature
+            | Self::AuthFailTokenRevoked
+            | Self::AuthFailApiKeyInvalid
+            | Self::AuthFailApiKeyExpired
+            | Self::AuthFailUnknownUser { .. }
             | Self::AuthFailCtxNotInRequestExt => (StatusCode::FORBIDDEN, ClientError::NO_AUTH),
+            Self::AuthFailExpired | Self::AuthFailSessionMaxLifetime => {
+                (StatusCode::FORBIDDEN, ClientError::SESSION_EXPIRED)
+            }
+            Self::AuthFailNoPermission { .. } => {
+                (StatusCode::FORBIDDEN, ClientError::NO_PERMISSION)
+            }
 
             // -- Model
-            Self::TicketDeleteFailIdNotFound { .. } => {
+            Self::TicketNotDeleted { .. }
+            | Self::TicketFailValidation { .. }
+            | Self::TicketListCursorInvalid
+            | Self::InvalidTransition { .. } => {
                 (StatusCode::BAD_REQUEST, ClientError::INVALID_PARAMS)
             }
+            Self::TicketAccessDenied { .. } | Self::CommentAccessDenied { .. } => {
+                (StatusCode::FORBIDDEN, ClientError::NO_PERMISSION)
+            }
+            Self::TicketNotFound { .. }
+            | Self::TicketDeleted { .. }
+            | Self::CommentNotFound { .. }
+            | Self::UserNotFound { .. }
+            | Self::ApiKeyNotFound { .. } => (StatusCode::NOT_FOUND, ClientError::ENTITY_NOT_FOUND),
+            Self::TicketVersionConflict { .. } => (
+                StatusCode::PRECONDITION_FAILED,
+                ClientError::VERSION_CONFLICT,
+            ),
+            Self::UserUsernameAlreadyExists { .. } => {
+                (StatusCode::CONFLICT, ClientError::USERNAME_TAKEN)
+            }
 
             // -- Fallback (should not techincally happen)
             _ => (
@@ -68,13 +213,132 @@ impl Error {
             ),
         }
     }
+
+    /// Error details that are safe to send back to the client
+    /// (e.g., which fields are invalid and why).
+    pub fn client_detail(&self) -> Option<Value> {
+        match self {
+            Self::RegisterFailValidation { errors }
+            | Self::TicketFailValidation { errors }
+            | Self::BodyFailJson { errors }
+            | Self::BodyFailValidation { errors }
+            | Self::QueryFailParams { errors }
+            | Self::PathFailParams { errors } => serde_json::to_value(errors).ok(),
+            Self::InvalidTransition { from, to } => Some(json!({
+                "from": from,
+                "to": to,
+                "allowed": from.transitions(),
+            })),
+            Self::TicketVersionConflict { actual, .. } => Some(json!({ "version": actual })),
+            _ => None,
+        }
+    }
+
+    /// Human readable explanation of this occurrence, for the problem+json `detail`.
+    pub fn client_message(&self) -> Option<String> {
+        let message = match self {
+            Self::RegisterFailValidation { .. }
+            | Self::TicketFailValidation { .. }
+            | Self::BodyFailValidation { .. } => "Some fields are invalid.".to_string(),
+            Self::BodyFailJson { .. } => "The JSON body could not be read.".to_string(),
+            Self::QueryFailParams { .. } => "Some query parameters are invalid.".to_string(),
+            Self::PathFailParams { .. } => "Some path parameters are invalid.".to_string(),
+            Self::TicketListCursorInvalid => "The cursor is not valid for this list.".to_string(),
+            Self::TicketNotFound { id } => format!("No ticket with id {id}."),
+            Self::TicketDeleted { id } => format!("The ticket {id} is deleted."),
+            Self::TicketNotDeleted { id } => format!("The ticket {id} is not deleted."),
+            Self::TicketVersionConflict {
+                id,
+                expected,
+                actual,
+            } => match expected.as_slice() {
+                [expected] => format!("The ticket {id} is at version {actual}, not {expected}."),
+                _ => format!("The ticket {id} is at version {actual}, none of the expected ones."),
+            },
+            Self::InvalidTransition { from, to } => format!(
+                "A ticket cannot go from '{}' to '{}'.",
+                from.as_ref(),
+                to.as_ref()
+            ),
+            Self::CommentNotFound { id } => format!("No comment with id {id}."),
+            Self::UserNotFound { id } => format!("No user with id {id}."),
+            Self::UserUsernameAlreadyExists { username } => {
+                format!("The username '{username}' is already taken.")
+            }
+            Self::ApiKeyNotFound { id } => format!("No API key with id {id}."),
+            _ => return None,
+        };
+
+        Some(message)
+    }
+
+    /// The error data (e.g., `{"id": 3}`) and detail, for the problem+json extension members.
+    /// None for the errors that could leak internals or help guessing credentials.
+    pub fn client_data(&self) -> Option<Value> {
+        let (_, client_error) = self.client_status_and_error();
+        if matches!(
+            client_error,
+            ClientError::LOGIN_FAIL
+                | ClientError::NO_AUTH
+                | ClientError::SESSION_EXPIRED
+                | ClientError::SERVICE_ERROR
+        ) {
+            return None;
+        }
+
+        let mut data = serde_json::to_value(self).ok()?.get_mut("data")?.take();
+        if let (Value::Object(data), Some(Value::Object(detail))) =
+            (&mut data, self.client_detail())
+        {
+            data.extend(detail);
+        }
+
+        Some(data)
+    }
 }
 
+/// The error types of the client responses, a stable API.
 #[derive(Debug, strum_macros::AsRefStr)]
 #[allow(non_camel_case_types)]
 pub enum ClientError {
     LOGIN_FAIL,
     NO_AUTH,
+    NO_PERMISSION,
+    SESSION_EXPIRED,
+    ENTITY_NOT_FOUND,
     INVALID_PARAMS,
+    USERNAME_TAKEN,
+    VERSION_CONFLICT,
     SERVICE_ERROR,
 }
+
+// Problem catalog (RFC 7807), the type URIs must never change
+impl ClientError {
+    pub fn problem_type(&self) -> &'static str {
+        match self {
+            Self::LOGIN_FAIL => "/problems/login-fail",
+            Self::NO_AUTH => "/problems/no-auth",
+            Self::NO_PERMISSION => "/problems/no-permission",
+            Self::SESSION_EXPIRED => "/problems/session-expired",
+            Self::ENTITY_NOT_FOUND => "/problems/entity-not-found",
+            Self::INVALID_PARAMS => "/problems/invalid-params",
+            Self::USERNAME_TAKEN => "/problems/username-taken",
+            Self::VERSION_CONFLICT => "/problems/version-conflict",
+            Self::SERVICE_ERROR => "/problems/service-error",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::LOGIN_FAIL => "Login failed",
+            Self::NO_AUTH => "Not authenticated",
+            Self::NO_PERMISSION => "Permission denied",
+            Self::SESSION_EXPIRED => "Session expired",
+            Self::ENTITY_NOT_FOUND => "Entity not found",
+            Self::INVALID_PARAMS => "Invalid parameters",
+            Self::USERNAME_TAKEN => "Username taken",
+            Self::VERSION_CONFLICT => "Version conflict",
+            Self::SERVICE_ERROR => "Service error",
+        }
+    }
+}
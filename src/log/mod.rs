@@ -0,0 +1,199 @@
+//! Request log lines, handed to a writer thread that feeds the configured sink
+//! (and the recent lines), so that logging never blocks the response path.
+
+mod recent;
+mod sink;
+
+pub use self::recent::RequestLogFilter;
+pub use self::sink::RequestLogKind;
+
+use std::sync::OnceLock;
+use std::thread;
+
+use axum::body::HttpBody;
+use axum::http::{HeaderMap, Method, Uri, header};
+use axum::response::Response;
+use serde::Serialize;
+use serde_json::Value;
+use serde_json::json;
+use serde_with::{serde_as, skip_serializing_none};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use tokio::sync::mpsc::{self, Receiver, Sender, error::TrySendError};
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use self::recent::RecentRequestLogs;
+use self::sink::{RequestLogSink, new_sink};
+use crate::config::config;
+use crate::utils::now_utc;
+use crate::web::mw_req_stamp::ReqStamp;
+use crate::{Error, Result, ctx::Ctx};
+
+/// Lines waiting for the sink, beyond it they are dropped.
+const REQUEST_LOG_BUFFER: usize = 4096;
+
+static REQUEST_LOG_TX: OnceLock<Sender<RequestLogLine>> = OnceLock::new();
+static RECENT_REQUEST_LOGS: OnceLock<RecentRequestLogs> = OnceLock::new();
+
+/// Logs the request with the response sent (`res`), and its service error if any.
+/// Never waits, the line is written in the background.
+pub fn log_request(
+    req_stamp: &ReqStamp,
+    req_method: Method,
+    uri: Uri,
+    req_headers: &HeaderMap,
+    ctx: Option<Ctx>,
+    res: &Response,
+    service_error: Option<&Error>,
+) -> Result<()> {
+    let latency = now_utc() - req_stamp.time_in;
+
+    let client_error = service_error.map(|se| se.client_status_and_error().1);
+    let error_type = service_error.map(|se| se.as_ref().to_string());
+    let error_data = serde_json::to_value(service_error)
+        .ok()
+        .and_then(|mut v| v.get_mut("data").map(|v| v.take()));
+
+    // Create the RequestLogLine
+    let log_line = RequestLogLine {
+        uuid: req_stamp.uuid,
+        timestamp: req_stamp.time_in,
+
+        user_id: ctx.map(|c| c.user_id()),
+        client_ip: req_stamp.client_ip.map(|ip| ip.to_string()),
+        user_agent: header_str(req_headers, header::USER_AGENT).map(str::to_string),
+
+        req_path: uri.to_string(),
+        req_method: req_method.to_string(),
+        req_bytes: header_str(req_headers, header::CONTENT_LENGTH).and_then(|v| v.parse().ok()),
+
+        res_status: res.status().as_u16(),
+        res_bytes: res.body().size_hint().exact().or_else(|| {
+            header_str(res.headers(), header::CONTENT_LENGTH).and_then(|v| v.parse().ok())
+        }),
+        latency_ms: latency.whole_microseconds() as f64 / 1000.,
+
+        client_error_type: client_error.map(|e| e.as_ref().to_string()),
+
+        error_type,
+        error_data,
+    };
+
+    send_line(log_line);
+
+    Ok(())
+}
+
+fn header_str(headers: &HeaderMap, name: header::HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+// region: --- Request Log Writer
+/// Starts the writer thread on the configured sink, once at startup.
+pub fn init_request_log() -> Result<()> {
+    let sink = new_sink(config())?;
+    RECENT_REQUEST_LOGS
+        .set(RecentRequestLogs::new(config().request_log_recent))
+        .map_err(|_| Error::RequestLogFail("already initialized".to_string()))?;
+    let (tx, rx) = mpsc::channel(REQUEST_LOG_BUFFER);
+
+    thread::Builder::new()
+        .name("request-log".to_string())
+        .spawn(move || write_lines(rx, sink))
+        .map_err(|ex| Error::RequestLogFail(ex.to_string()))?;
+
+    REQUEST_LOG_TX
+        .set(tx)
+        .map_err(|_| Error::RequestLogFail("already initialized".to_string()))
+}
+
+fn send_line(line: RequestLogLine) {
+    let Some(tx) = REQUEST_LOG_TX.get() else {
+        warn!(
+            "{:<12} - request log not initialized - {}",
+            "REQ_LOG", line.uuid
+        );
+        return;
+    };
+
+    match tx.try_send(line) {
+        Ok(()) => (),
+        Err(TrySendError::Full(line)) => {
+            warn!(
+                "{:<12} - sink behind, line dropped - {}",
+                "REQ_LOG", line.uuid
+            );
+        }
+        Err(TrySendError::Closed(line)) => {
+            error!(
+                "{:<12} - writer stopped, line dropped - {}",
+                "REQ_LOG", line.uuid
+            );
+        }
+    }
+}
+
+/// Writes the lines as they come, flushing the sink after each batch,
+/// then keeps them with the recent ones.
+fn write_lines(mut rx: Receiver<RequestLogLine>, mut sink: Box<dyn RequestLogSink>) {
+    while let Some(line) = rx.blocking_recv() {
+        let mut batch = vec![line];
+        while let Ok(line) = rx.try_recv() {
+            batch.push(line);
+        }
+
+        for line in &batch {
+            if let Err(ex) = sink.write_line(&json!(line).to_string()) {
+                error!("{:<12} - write_line - {ex}", "REQ_LOG");
+            }
+        }
+        if let Err(ex) = sink.flush() {
+            error!("{:<12} - flush - {ex}", "REQ_LOG");
+        }
+
+        if let Some(recent) = RECENT_REQUEST_LOGS.get() {
+            batch.into_iter().for_each(|line| recent.push(line));
+        }
+    }
+}
+
+/// The recent request log lines matching the filter, newest first
+/// (only the last `SERVICE_REQUEST_LOG_RECENT` ones are kept).
+pub fn list_recent_request_logs(filter: &RequestLogFilter) -> Result<Vec<RequestLogLine>> {
+    let recent = RECENT_REQUEST_LOGS
+        .get()
+        .ok_or(Error::RequestLogFail("not initialized".to_string()))?;
+
+    Ok(recent.list(filter))
+}
+// endregion: --- Request Log Writer
+
+#[serde_as]
+#[skip_serializing_none] // Option::None doesn't get serialized
+#[derive(Clone, Debug, Serialize)]
+pub struct RequestLogLine {
+    uuid: Uuid,
+    #[serde_as(as = "Rfc3339")]
+    timestamp: OffsetDateTime, // iso8601 format, of the request arrival
+
+    // -- User and context attributes
+    user_id: Option<u64>,
+    client_ip: Option<String>,
+    user_agent: Option<String>,
+
+    // -- http request attributes
+    req_path: String,
+    req_method: String,
+    req_bytes: Option<u64>, // body, from its `Content-Length`
+
+    // -- http response attributes
+    res_status: u16,
+    res_bytes: Option<u64>, // body, None when streamed without a length
+    latency_ms: f64,
+
+    // -- Error attributes
+    client_error_type: Option<String>,
+    error_type: Option<String>,
+    error_data: Option<Value>,
+}
@@ -0,0 +1,92 @@
+//! The most recent request log lines, kept in memory for the admin API
+//! (e.g., what did a user do in the last minutes).
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use serde_with::serde_as;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::RequestLogLine;
+
+pub const RECENT_LIMIT_DEFAULT: usize = 100;
+pub const RECENT_LIMIT_MAX: usize = 1000;
+
+#[serde_as]
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct RequestLogFilter {
+    pub user_id: Option<u64>,
+    pub error_type: Option<String>, // service or client one, e.g., `AuthFailExpired` or `NO_AUTH`
+    pub path_prefix: Option<String>,
+    #[serde_as(as = "Option<Rfc3339>")]
+    #[serde(default)]
+    pub from: Option<OffsetDateTime>, // inclusive
+    #[serde_as(as = "Option<Rfc3339>")]
+    #[serde(default)]
+    pub to: Option<OffsetDateTime>, // exclusive
+    pub req_uuid: Option<Uuid>,
+    #[validate(range(min = 1, max = RECENT_LIMIT_MAX))]
+    pub limit: Option<usize>,
+}
+
+impl RequestLogFilter {
+    fn matches(&self, line: &RequestLogLine) -> bool {
+        self.user_id.is_none_or(|id| line.user_id == Some(id))
+            && self.error_type.as_deref().is_none_or(|error_type| {
+                line.error_type.as_deref() == Some(error_type)
+                    || line.client_error_type.as_deref() == Some(error_type)
+            })
+            && self
+                .path_prefix
+                .as_deref()
+                .is_none_or(|prefix| line.req_path.starts_with(prefix))
+            && self.from.is_none_or(|from| line.timestamp >= from)
+            && self.to.is_none_or(|to| line.timestamp < to)
+            && self.req_uuid.is_none_or(|uuid| line.uuid == uuid)
+    }
+}
+
+/// Bounded, the oldest lines are evicted first.
+pub struct RecentRequestLogs {
+    capacity: usize,
+    lines: Mutex<VecDeque<RequestLogLine>>,
+}
+
+impl RecentRequestLogs {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            lines: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub fn push(&self, line: RequestLogLine) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut lines = self.lines.lock().unwrap();
+        if lines.len() == self.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    /// The matching lines, newest first.
+    pub fn list(&self, filter: &RequestLogFilter) -> Vec<RequestLogLine> {
+        let limit = filter.limit.unwrap_or(RECENT_LIMIT_DEFAULT);
+
+        let lines = self.lines.lock().unwrap();
+        lines
+            .iter()
+            .rev()
+            .filter(|line| filter.matches(line))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
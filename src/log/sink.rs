@@ -0,0 +1,215 @@
+//! Where the request log lines go, chosen by `SERVICE_REQUEST_LOG`.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use time::format_description::FormatItem;
+use time::macros::format_description;
+use tracing::info;
+
+use crate::config::Config;
+use crate::utils::now_utc;
+use crate::{Error, Result};
+
+/// Receives the request log lines (one JSON object each), from the writer thread.
+pub trait RequestLogSink: Send {
+    fn write_line(&mut self, line: &str) -> io::Result<()>;
+
+    /// Called after each batch of lines.
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+// region: --- Sink Selection
+#[derive(Clone, Copy, Debug)]
+pub enum RequestLogKind {
+    Stdout,
+    File,
+}
+
+impl FromStr for RequestLogKind {
+    type Err = Error;
+
+    fn from_str(kind: &str) -> Result<Self> {
+        match kind {
+            "stdout" => Ok(Self::Stdout),
+            "file" => Ok(Self::File),
+            _ => Err(Error::ConfigWrongFormat("SERVICE_REQUEST_LOG")),
+        }
+    }
+}
+
+pub fn new_sink(config: &Config) -> Result<Box<dyn RequestLogSink>> {
+    Ok(match config.request_log {
+        RequestLogKind::Stdout => Box::new(StdoutSink),
+        RequestLogKind::File => Box::new(FileSink::open(
+            &config.request_log_path,
+            FileRotation {
+                max_bytes: config.request_log_max_bytes,
+                max_age: Duration::from_secs(config.request_log_rotate_sec),
+                keep_files: config.request_log_keep_files,
+            },
+        )?),
+    })
+}
+// endregion: --- Sink Selection
+
+// region: --- Stdout Sink
+/// On the `request_log` tracing target, so with the service logs
+/// (and their `RUST_LOG` filtering).
+pub struct StdoutSink;
+
+impl RequestLogSink for StdoutSink {
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        info!(target: "request_log", "{line}");
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+// endregion: --- Stdout Sink
+
+// region: --- File Sink
+pub struct FileRotation {
+    pub max_bytes: u64,    // rotate before exceeding it
+    pub max_age: Duration, // rotate once the file is that old
+    pub keep_files: usize, // rotated files kept, the oldest are removed
+}
+
+/// Append-only JSON lines file, e.g. `logs/requests.jsonl`, rotated to
+/// `logs/requests.20261017T091000.123Z.jsonl`.
+pub struct FileSink {
+    path: PathBuf,
+    rotation: FileRotation,
+    writer: BufWriter<File>,
+    size: u64,
+    created_at: SystemTime, // of the file, so its age carries over restarts
+}
+
+const ROTATED_SUFFIX_FORMAT: &[FormatItem<'static>] =
+    format_description!("[year][month][day]T[hour][minute][second].[subsecond digits:3]Z");
+
+impl FileSink {
+    pub fn open(path: impl AsRef<Path>, rotation: FileRotation) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(log_file_error)?;
+        }
+
+        let (writer, size, created_at) = open_append(&path).map_err(log_file_error)?;
+
+        Ok(Self {
+            path,
+            rotation,
+            writer,
+            size,
+            created_at,
+        })
+    }
+
+    fn should_rotate(&self, line_len: u64) -> bool {
+        self.size > 0
+            && (self.size + line_len > self.rotation.max_bytes
+                || self.created_at.elapsed().unwrap_or_default() >= self.rotation.max_age)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.writer.flush()?;
+
+        let suffix = now_utc()
+            .format(ROTATED_SUFFIX_FORMAT)
+            .map_err(io::Error::other)?;
+        fs::rename(&self.path, self.rotated_path(&suffix))?;
+
+        (self.writer, self.size, self.created_at) = open_append(&self.path)?;
+
+        self.remove_expired()
+    }
+
+    fn rotated_path(&self, suffix: &str) -> PathBuf {
+        let (stem, ext) = self.stem_and_ext();
+        self.path.with_file_name(format!("{stem}.{suffix}.{ext}"))
+    }
+
+    /// Keeps the `keep_files` most recent rotated files (their suffixes sort by time).
+    fn remove_expired(&self) -> io::Result<()> {
+        let (stem, ext) = self.stem_and_ext();
+        let (prefix, suffix) = (format!("{stem}."), format!(".{ext}"));
+        let dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+
+        let mut rotated: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| {
+                        name.len() > prefix.len() + suffix.len()
+                            && name.starts_with(&prefix)
+                            && name.ends_with(&suffix)
+                    })
+            })
+            .collect();
+        rotated.sort();
+
+        let expired = rotated.len().saturating_sub(self.rotation.keep_files);
+        for path in &rotated[..expired] {
+            fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+
+    fn stem_and_ext(&self) -> (&str, &str) {
+        let stem = self
+            .path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("requests");
+        let ext = self
+            .path
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or("jsonl");
+        (stem, ext)
+    }
+}
+
+impl RequestLogSink for FileSink {
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let line_len = line.len() as u64 + 1;
+        if self.should_rotate(line_len) {
+            self.rotate()?;
+        }
+
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.write_all(b"\n")?;
+        self.size += line_len;
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// The file writer, size and creation time (the modification one if the platform has none).
+fn open_append(path: &Path) -> io::Result<(BufWriter<File>, u64, SystemTime)> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let metadata = file.metadata()?;
+    let created_at = metadata.created().or_else(|_| metadata.modified())?;
+
+    Ok((BufWriter::new(file), metadata.len(), created_at))
+}
+
+fn log_file_error(err: io::Error) -> Error {
+    Error::RequestLogFail(err.to_string())
+}
+// endregion: --- File Sink
@@ -1,41 +1,67 @@
-use crate::{ctx::Ctx, log::log_request, model::ModelController};
+use crate::{
+    ctx::Ctx,
+    log::log_request,
+    model::ModelController,
+    web::mw_req_stamp::ReqStamp,
+    web::problem::{ErrorFormat, client_error_response},
+};
 
 pub use self::error::{Error, Result}; // Best practice
 
 use std::net::SocketAddr;
 
 use axum::{
-    Json, Router,
+    Router,
     extract::{Path, Query},
-    http::{Method, Uri},
+    http::{HeaderMap, Method, Uri},
     middleware,
     response::{Html, IntoResponse, Response},
     routing::{get, get_service},
 };
 use serde::Deserialize;
-use serde_json::json;
 use tokio::net::TcpListener;
 use tower_cookies::CookieManagerLayer;
 use tower_http::services::ServeDir;
-use uuid::Uuid;
+use tracing::{debug, info};
+use tracing_subscriber::EnvFilter;
 
+mod config;
+mod crypt;
 mod ctx;
 mod error;
 mod log;
 mod model;
+mod utils;
 mod web;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Levels by `RUST_LOG` (e.g., `RUST_LOG=rust_axum_backend=debug,info`), `info` by default
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+        )
+        .init();
+
+    // Fail fast on missing/invalid configuration
+    config::config();
+
+    log::init_request_log()?;
+
     // Initialize ModelController
     let mc = ModelController::new().await?;
 
-    let routes_apis = web::routes_ticket::routes(mc.clone())
+    let routes_apis = Router::new()
+        .merge(web::routes_ticket::routes(mc.clone()))
+        .merge(web::routes_comment::routes(mc.clone()))
+        .merge(web::routes_user::routes(mc.clone()))
+        .merge(web::routes_api_key::routes(mc.clone()))
+        .merge(web::routes_admin::routes())
         .route_layer(middleware::from_fn(web::mw_auth::mw_require_auth));
 
     let routes_all = Router::new()
         .merge(routes_hello())
-        .merge(web::routes_login::routes())
+        .merge(web::routes_login::routes(mc.clone()))
         .nest("/api", routes_apis)
         .layer(middleware::map_response(main_response_mapper))
         .layer(middleware::from_fn_with_state(
@@ -43,17 +69,21 @@ async fn main() -> Result<()> {
             web::mw_auth::mw_ctx_resolver,
         ))
         .layer(CookieManagerLayer::new())
+        .layer(middleware::from_fn(web::mw_req_stamp::mw_req_stamp))
         .fallback_service(get_service(ServeDir::new("./")));
     // .handle_error(handle_error);
 
     // region: --- Start Server
     let addr = SocketAddr::from(([127, 0, 0, 1], 8080));
-    println!("LISTENING ON {addr}\n");
+    info!("{:<12} - {addr}", "LISTENING");
 
     let listener = TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, routes_all.into_make_service())
-        .await
-        .unwrap();
+    axum::serve(
+        listener,
+        routes_all.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
     // endregion: --- Start Server
 
     Ok(())
@@ -61,40 +91,43 @@ async fn main() -> Result<()> {
 
 async fn main_response_mapper(
     ctx: Result<Ctx>,
+    req_stamp: ReqStamp,
     uri: Uri,
     req_method: Method,
+    headers: HeaderMap,
     res: Response,
 ) -> Response {
-    println!("->> {:<12} - main_response_mapper", "RES_MAPPER");
+    debug!("{:<12} - main_response_mapper", "RES_MAPPER");
     let ctx = ctx.ok();
-    let uuid = Uuid::new_v4();
 
     // -- Get the eventual response error
-    let service_error = res.extensions().get::<Error>();
-    let client_status_error = service_error.map(|e| e.client_status_and_error());
-
-    // -- If client error, build the new response
-    let error_response = client_status_error
-        .as_ref()
-        .map(|(status_code, client_error)| {
-            let client_error_body = json!({
-                "error": {
-                    "type": client_error.as_ref(),
-                    "req_uuid": uuid.to_string(),
-                }
-            });
-            println!("    ->> client_error_body: {client_error_body}");
-
-            // Build the new response from the client_error_body
-            (*status_code, Json(client_error_body)).into_response()
-        });
-
-    // Build and log the server log line
-    let client_error = client_status_error.unzip().1;
-    let _ = log_request(uuid, req_method, uri, ctx, service_error, client_error).await;
-
-    println!();
-    error_response.unwrap_or(res)
+    let service_error = res.extensions().get::<Error>().cloned();
+    let client_status_error = service_error.as_ref().map(|e| e.client_status_and_error());
+
+    // -- If client error, build the new response (in the format the client accepts)
+    let res = match client_status_error {
+        Some((status_code, client_error)) => client_error_response(
+            ErrorFormat::negotiate(&headers),
+            req_stamp.uuid,
+            status_code,
+            &client_error,
+            service_error.as_ref(),
+        ),
+        None => res,
+    };
+
+    // Build and log the server log line (of the response sent)
+    let _ = log_request(
+        &req_stamp,
+        req_method,
+        uri,
+        &headers,
+        ctx,
+        &res,
+        service_error.as_ref(),
+    );
+
+    res
 }
 
 fn routes_hello() -> Router {
@@ -110,7 +143,7 @@ struct HelloParams {
 
 // e.g. `/hello?name=Person1`
 async fn handler_hello(Query(params): Query<HelloParams>) -> impl IntoResponse {
-    println!("->> {:<12} - handler_hello - {params:?}", "HANDLER");
+    debug!("{:<12} - handler_hello - {params:?}", "HANDLER");
 
     let name = params.name.as_deref().unwrap_or("World!");
     Html(format!("<h1>Hello <strong>{name}</strong></h1>"))
@@ -118,7 +151,7 @@ async fn handler_hello(Query(params): Query<HelloParams>) -> impl IntoResponse {
 
 // e.g. `/hello2/Person2`
 async fn handler_hello2(Path(name): Path<String>) -> impl IntoResponse {
-    println!("->> {:<12} - handler_hello2 - {name:?}", "HANDLER");
+    debug!("{:<12} - handler_hello2 - {name:?}", "HANDLER");
     Html(format!("<h1>Hello <strong>{name}</strong></h1>"))
 }
 
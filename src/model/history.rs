@@ -0,0 +1,81 @@
+//! Ticket change history (audit trail), recorded by the ModelController on every mutation.
+//!
+//! Changes are diffed on the ticket JSON, so new ticket fields are tracked as they come.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use serde_with::serde_as;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+use crate::model::Ticket;
+use crate::{Error, Result};
+
+// region: --- History Types
+#[serde_as]
+#[derive(Clone, Debug, Serialize)]
+pub struct HistoryEntry {
+    pub id: u64,
+    pub ticket_id: u64,
+    pub actor: u64, // user_id
+    #[serde_as(as = "Rfc3339")]
+    pub time: OffsetDateTime,
+    pub action: HistoryAction,
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, strum_macros::AsRefStr)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum HistoryAction {
+    Created, // changes from null
+    Updated,
+    Deleted, // soft delete
+    Restored,
+    Purged, // changes to null, i.e., the last state of the ticket
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+// endregion: --- History Types
+
+/// Field level diff between two states of a ticket (None for before creation or after deletion).
+pub fn ticket_changes(before: Option<&Ticket>, after: Option<&Ticket>) -> Result<Vec<FieldChange>> {
+    let mut before = ticket_fields(before)?;
+    let mut after = ticket_fields(after)?;
+
+    let mut fields: Vec<String> = before.keys().chain(after.keys()).cloned().collect();
+    fields.sort();
+    fields.dedup();
+
+    let changes = fields
+        .into_iter()
+        .filter(|field| field != "id" && field != "version")
+        .filter_map(|field| {
+            let before = before.remove(&field).unwrap_or_default();
+            let after = after.remove(&field).unwrap_or_default();
+            (before != after).then_some(FieldChange {
+                field,
+                before,
+                after,
+            })
+        })
+        .collect();
+
+    Ok(changes)
+}
+
+fn ticket_fields(ticket: Option<&Ticket>) -> Result<Map<String, Value>> {
+    let Some(ticket) = ticket else {
+        return Ok(Map::new());
+    };
+
+    match serde_json::to_value(ticket) {
+        Ok(Value::Object(fields)) => Ok(fields),
+        _ => Err(Error::StoreFail("ticket is not a JSON object".to_string())),
+    }
+}
@@ -0,0 +1,247 @@
+//! Ticket list pagination, the stores returning the pages of a `TicketListQuery`
+//! (e.g., in SQL), or through the generic `paginate`.
+//!
+//! The cursor is the sort key of the last returned ticket (keyset pagination),
+//! so pages stay consistent when tickets are created or deleted in between.
+
+use std::cmp::Ordering;
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::model::{AssigneeFilter, Ticket, TicketFilter, TicketPriority};
+use crate::{Error, Result};
+
+pub const LIST_LIMIT_DEFAULT: usize = 50;
+pub const LIST_LIMIT_MAX: usize = 200;
+
+// region: --- List Types
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TicketSort {
+    #[default]
+    Id,
+    Title,
+    Ctime,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct TicketListOptions {
+    #[validate(range(min = 1, max = LIST_LIMIT_MAX))]
+    pub limit: Option<usize>,
+    pub cursor: Option<String>, // `next_cursor` of the previous page
+    #[serde(default)]
+    pub sort: TicketSort,
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TicketPage {
+    pub items: Vec<Ticket>,
+    pub next_cursor: Option<String>, // None for the last page
+}
+
+/// The filter and options of a list request, checked and resolved for the stores.
+#[derive(Clone, Debug)]
+pub struct TicketListQuery {
+    pub readable_by: Option<u64>, // created by or assigned to this user
+    pub cid: Option<u64>,
+    pub title_contains: Option<String>, // lowercased (ASCII, as SQLite `lower`)
+    pub assignee: Option<Option<u64>>,  // `Some(None)` for the unassigned ones
+    pub label: Option<String>,
+    pub priority: Option<TicketPriority>,
+    pub include_deleted: bool,
+    pub sort: TicketSort,
+    pub order: SortOrder,
+    pub cursor: Option<TicketCursor>,
+    pub limit: usize,
+}
+
+impl TicketListQuery {
+    /// `readable_by` is the user the tickets are restricted to (if any),
+    /// `user_id` the ctx user (for `assignee=me`).
+    pub fn new(
+        readable_by: Option<u64>,
+        user_id: u64,
+        filter: TicketFilter,
+        options: TicketListOptions,
+    ) -> Result<Self> {
+        let TicketListOptions {
+            limit,
+            cursor,
+            sort,
+            order,
+        } = options;
+
+        let limit = limit.unwrap_or(LIST_LIMIT_DEFAULT);
+
+        let cursor = cursor.as_deref().map(TicketCursor::decode).transpose()?;
+        // A cursor is only valid for the sort it was made for
+        if cursor
+            .as_ref()
+            .is_some_and(|c| c.sort != sort || c.order != order)
+        {
+            return Err(Error::TicketListCursorInvalid);
+        }
+
+        Ok(Self {
+            readable_by,
+            cid: filter.cid,
+            title_contains: filter.title_contains.map(|t| t.to_ascii_lowercase()),
+            assignee: filter.assignee.map(|assignee| match assignee {
+                AssigneeFilter::Me => Some(user_id),
+                AssigneeFilter::Unassigned => None,
+                AssigneeFilter::User(id) => Some(id),
+            }),
+            label: filter.label,
+            priority: filter.priority,
+            include_deleted: filter.include_deleted,
+            sort,
+            order,
+            cursor,
+            limit,
+        })
+    }
+
+    /// Whether the ticket passes the filter (the cursor aside).
+    pub fn matches(&self, ticket: &Ticket) -> bool {
+        self.readable_by
+            .is_none_or(|user_id| ticket.cid == user_id || ticket.assignee == Some(user_id))
+            && self.cid.is_none_or(|cid| ticket.cid == cid)
+            && self
+                .title_contains
+                .as_ref()
+                .is_none_or(|part| ticket.title.to_ascii_lowercase().contains(part.as_str()))
+            && self
+                .assignee
+                .is_none_or(|assignee| ticket.assignee == assignee)
+            && self
+                .label
+                .as_ref()
+                .is_none_or(|label| ticket.labels.contains(label))
+            && self.priority.is_none_or(|p| ticket.priority == p)
+            && (self.include_deleted || ticket.deleted_at.is_none())
+    }
+}
+// endregion: --- List Types
+
+// region: --- Cursor
+/// Sort key of the last ticket of a page, the id breaking the ties.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TicketCursor {
+    sort: TicketSort,
+    order: SortOrder,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ctime: Option<i128>, // unix nanoseconds
+    pub id: u64,
+}
+
+impl TicketCursor {
+    fn new(ticket: &Ticket, sort: TicketSort, order: SortOrder) -> Self {
+        Self {
+            sort,
+            order,
+            title: (sort == TicketSort::Title).then(|| ticket.title.clone()),
+            ctime: (sort == TicketSort::Ctime).then(|| ticket.ctime.unix_timestamp_nanos()),
+            id: ticket.id,
+        }
+    }
+
+    fn encode(&self) -> Result<String> {
+        let json = serde_json::to_vec(self).map_err(|_| Error::TicketListCursorInvalid)?;
+
+        Ok(URL_SAFE_NO_PAD.encode(json))
+    }
+
+    fn decode(cursor: &str) -> Result<Self> {
+        let json = URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(|_| Error::TicketListCursorInvalid)?;
+
+        serde_json::from_slice(&json).map_err(|_| Error::TicketListCursorInvalid)
+    }
+
+    /// Position of the ticket relative to the cursor, in ascending order.
+    fn cmp_ticket(&self, ticket: &Ticket) -> Ordering {
+        let key_ord = match self.sort {
+            TicketSort::Id => Ordering::Equal,
+            TicketSort::Title => self
+                .title
+                .as_deref()
+                .map_or(Ordering::Equal, |title| ticket.title.as_str().cmp(title)),
+            TicketSort::Ctime => self.ctime.map_or(Ordering::Equal, |ctime| {
+                ticket.ctime.unix_timestamp_nanos().cmp(&ctime)
+            }),
+        };
+
+        key_ord.then(ticket.id.cmp(&self.id))
+    }
+}
+// endregion: --- Cursor
+
+/// Filter and sort the tickets, and return the page after the query cursor
+/// (for the stores without queries, e.g., the memory one).
+pub fn paginate(tickets: Vec<Ticket>, query: &TicketListQuery) -> Result<TicketPage> {
+    let TicketListQuery {
+        sort,
+        order,
+        cursor,
+        ..
+    } = query;
+    let (sort, order) = (*sort, *order);
+
+    let mut tickets: Vec<Ticket> = tickets.into_iter().filter(|t| query.matches(t)).collect();
+
+    let by_sort = |a: &Ticket, b: &Ticket| {
+        let key_ord = match sort {
+            TicketSort::Id => Ordering::Equal,
+            TicketSort::Title => a.title.cmp(&b.title),
+            TicketSort::Ctime => a.ctime.cmp(&b.ctime),
+        };
+        key_ord.then(a.id.cmp(&b.id))
+    };
+    match order {
+        SortOrder::Asc => tickets.sort_by(by_sort),
+        SortOrder::Desc => tickets.sort_by(|a, b| by_sort(b, a)),
+    }
+
+    if let Some(cursor) = cursor {
+        let after = match order {
+            SortOrder::Asc => Ordering::Greater,
+            SortOrder::Desc => Ordering::Less,
+        };
+        tickets.retain(|t| cursor.cmp_ticket(t) == after);
+    }
+    tickets.truncate(query.limit + 1);
+
+    page(tickets, query)
+}
+
+/// The page of the tickets after the query cursor, in order,
+/// one more than the limit if there are more (e.g., `LIMIT n + 1` in SQL).
+pub fn page(mut tickets: Vec<Ticket>, query: &TicketListQuery) -> Result<TicketPage> {
+    let has_more = tickets.len() > query.limit;
+    tickets.truncate(query.limit);
+
+    let next_cursor = match tickets.last() {
+        Some(last) if has_more => Some(TicketCursor::new(last, query.sort, query.order).encode()?),
+        _ => None,
+    };
+
+    Ok(TicketPage {
+        items: tickets,
+        next_cursor,
+    })
+}
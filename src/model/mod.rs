@@ -0,0 +1,999 @@
+//! Simplistic Model Layer
+//! (tickets in a pluggable store, see `store`)
+
+mod history;
+mod list;
+mod store;
+
+pub use self::history::{HistoryAction, HistoryEntry};
+pub use self::list::{TicketListOptions, TicketPage};
+
+use self::list::TicketListQuery;
+pub use self::store::StoreKind;
+
+use self::store::{Store, new_store};
+use crate::config::config;
+use crate::crypt::api_key::{ApiKeyToken, generate_api_key};
+use crate::crypt::pwd::hash_pwd;
+use crate::ctx::{Ctx, Permission, Role};
+use crate::error::FieldError;
+use crate::utils::{now_utc, now_utc_sec};
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_with::DisplayFromStr;
+use serde_with::serde_as;
+use std::collections::BTreeSet;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+const TITLE_LEN_MAX: u64 = 256;
+const LABELS_MAX: usize = 16;
+const LABEL_LEN_MAX: usize = 32;
+const COMMENT_LEN_MAX: u64 = 10_000;
+const API_KEY_NAME_LEN_MAX: u64 = 64;
+const TICKET_PURGE_INTERVAL: Duration = Duration::from_secs(3600);
+
+// region: --- Ticket Types
+#[serde_as]
+#[derive(Clone, Debug, Serialize)]
+pub struct Ticket {
+    pub id: u64,
+    pub cid: u64, // creator user_id
+    pub title: String,
+    pub status: TicketStatus,
+    pub assignee: Option<u64>, // user_id
+    pub priority: TicketPriority,
+    pub labels: BTreeSet<String>,
+    #[serde_as(as = "Option<Rfc3339>")]
+    pub due: Option<OffsetDateTime>,
+    #[serde_as(as = "Rfc3339")]
+    pub ctime: OffsetDateTime,
+    #[serde_as(as = "Option<Rfc3339>")]
+    pub deleted_at: Option<OffsetDateTime>, // soft deleted, until purged
+    pub deleted_by: Option<u64>,
+    pub version: u64, // incremented on every change, the web ETag
+}
+
+#[serde_as]
+#[derive(Deserialize, Validate)]
+pub struct TicketForCreate {
+    #[validate(length(max = TITLE_LEN_MAX), custom(function = "validate_not_blank"))]
+    pub title: String,
+    pub assignee: Option<u64>,
+    #[serde(default)]
+    pub priority: TicketPriority,
+    #[serde(default)]
+    pub labels: BTreeSet<String>,
+    #[serde_as(as = "Option<Rfc3339>")]
+    #[serde(default)]
+    pub due: Option<OffsetDateTime>,
+}
+
+/// Only the given fields are updated (`null` clears the optional ones).
+#[derive(Deserialize, Validate)]
+pub struct TicketForUpdate {
+    #[validate(length(max = TITLE_LEN_MAX), custom(function = "validate_not_blank"))]
+    pub title: Option<String>,
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub assignee: Option<Option<u64>>,
+    pub priority: Option<TicketPriority>,
+    pub labels: Option<BTreeSet<String>>,
+    #[serde(default, deserialize_with = "deserialize_some_rfc3339")]
+    pub due: Option<Option<OffsetDateTime>>,
+}
+
+#[serde_as]
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct TicketFilter {
+    #[serde(default)]
+    pub mine: bool, // only the tickets created by the ctx user
+    pub cid: Option<u64>,
+    pub title_contains: Option<String>, // case insensitive (ASCII)
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub assignee: Option<AssigneeFilter>,
+    pub label: Option<String>,
+    pub priority: Option<TicketPriority>,
+    #[serde(default)]
+    pub include_deleted: bool, // soft deleted tickets are hidden by default
+}
+
+/// `me`, `none` (unassigned), or a user id.
+#[derive(Clone, Copy, Debug)]
+pub enum AssigneeFilter {
+    Me,
+    Unassigned,
+    User(u64),
+}
+
+impl FromStr for AssigneeFilter {
+    type Err = String;
+
+    fn from_str(assignee: &str) -> core::result::Result<Self, Self::Err> {
+        match assignee {
+            "me" => Ok(Self::Me),
+            "none" => Ok(Self::Unassigned),
+            id => id
+                .parse()
+                .map(Self::User)
+                .map_err(|_| format!("'{id}' is not 'me', 'none' or a user id")),
+        }
+    }
+}
+
+#[derive(Deserialize, Validate)]
+pub struct TicketForTransition {
+    pub status: TicketStatus,
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, strum_macros::AsRefStr,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum TicketPriority {
+    Low,
+    #[default]
+    Medium,
+    High,
+    Urgent,
+}
+
+/// Present as `null` is `Some(None)`, absent is `None` (with `#[serde(default)]`).
+fn deserialize_some_rfc3339<'de, D>(
+    deserializer: D,
+) -> core::result::Result<Option<Option<OffsetDateTime>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    time::serde::rfc3339::option::deserialize(deserializer).map(Some)
+}
+// endregion: --- Ticket Types
+
+// region: --- Ticket Status
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, strum_macros::AsRefStr,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum TicketStatus {
+    #[default]
+    Open,
+    InProgress,
+    Blocked,
+    Resolved,
+    Closed,
+}
+
+impl TicketStatus {
+    /// The statuses a ticket can go to from this one.
+    pub fn transitions(&self) -> &'static [TicketStatus] {
+        use TicketStatus::*;
+
+        match self {
+            Open => &[InProgress, Blocked, Resolved, Closed],
+            InProgress => &[Open, Blocked, Resolved],
+            Blocked => &[Open, InProgress],
+            Resolved => &[InProgress, Closed],
+            Closed => &[Open], // reopen
+        }
+    }
+}
+// endregion: --- Ticket Status
+
+// region: --- Comment Types
+#[serde_as]
+#[derive(Clone, Debug, Serialize)]
+pub struct Comment {
+    pub id: u64,
+    pub ticket_id: u64,
+    pub author: u64, // user_id
+    pub body: String,
+    #[serde_as(as = "Rfc3339")]
+    pub ctime: OffsetDateTime,
+    #[serde_as(as = "Option<Rfc3339>")]
+    pub mtime: Option<OffsetDateTime>, // None until edited
+}
+
+#[derive(Deserialize, Validate)]
+pub struct CommentForCreate {
+    #[validate(length(max = COMMENT_LEN_MAX), custom(function = "validate_not_blank"))]
+    pub body: String,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct CommentForUpdate {
+    #[validate(length(max = COMMENT_LEN_MAX), custom(function = "validate_not_blank"))]
+    pub body: String,
+}
+// endregion: --- Comment Types
+
+// region: --- User Types
+#[serde_as]
+#[derive(Clone, Debug, Serialize)]
+pub struct User {
+    pub id: u64,
+    pub username: String,
+    #[serde(skip)]
+    pub pwd: String, // argon2 PHC string (embeds its own salt)
+    #[serde(skip)]
+    pub token_salt: Uuid,
+    pub roles: Vec<Role>,
+    #[serde_as(as = "Rfc3339")]
+    pub ctime: OffsetDateTime,
+}
+
+pub struct UserForCreate {
+    pub username: String,
+    pub pwd_clear: String,
+    pub roles: Vec<Role>,
+}
+// endregion: --- User Types
+
+// region: --- ApiKey Types
+#[serde_as]
+#[derive(Clone, Debug, Serialize)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub user_id: u64,
+    pub name: String,
+    #[serde(skip)]
+    pub key_hash: String, // HMAC of the key secret
+    pub scopes: Option<Vec<Permission>>, // None for all the user permissions
+    #[serde_as(as = "Option<Rfc3339>")]
+    pub exp: Option<OffsetDateTime>, // None for no expiration
+    #[serde_as(as = "Rfc3339")]
+    pub ctime: OffsetDateTime,
+}
+
+#[serde_as]
+#[derive(Deserialize, Validate)]
+pub struct ApiKeyForCreate {
+    #[validate(length(max = API_KEY_NAME_LEN_MAX), custom(function = "validate_not_blank"))]
+    pub name: String,
+    pub scopes: Option<Vec<Permission>>,
+    #[serde_as(as = "Option<Rfc3339>")]
+    #[serde(default)]
+    pub exp: Option<OffsetDateTime>,
+}
+// endregion: --- ApiKey Types
+
+// region: --- Model Controller
+#[derive(Clone)]
+pub struct ModelController {
+    store: Arc<dyn Store>, // tickets, users, revoked sessions and api keys
+}
+
+// Constructor
+impl ModelController {
+    pub async fn new() -> Result<Self> {
+        let mc = Self {
+            store: new_store(config().store, &config().db_path)?, // migrations applied here
+        };
+
+        mc.seed_admin().await?;
+        mc.spawn_ticket_purge();
+
+        Ok(mc)
+    }
+
+    /// Purge the soft deleted tickets past the retention period, periodically.
+    fn spawn_ticket_purge(&self) {
+        let mc = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(TICKET_PURGE_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(ex) = mc.purge_expired_tickets().await {
+                    error!("{:<12} - purge_expired_tickets - {ex:?}", "PURGE");
+                }
+            }
+        });
+    }
+
+    /// Create the first admin from the config when there are no users yet.
+    async fn seed_admin(&self) -> Result<()> {
+        if self.store.count_users().await? > 0 {
+            return Ok(());
+        }
+
+        let config = config();
+        self.create_user(UserForCreate {
+            username: config.admin_username.clone(),
+            pwd_clear: config.admin_pwd.clone(),
+            roles: vec![Role::Admin],
+        })
+        .await?;
+
+        Ok(())
+    }
+}
+
+// CRUD implementation
+impl ModelController {
+    pub async fn create_ticket(&self, ctx: Ctx, ticket_fc: TicketForCreate) -> Result<Ticket> {
+        check_in_scopes(&ctx, Permission::TicketCreate)?;
+
+        let ticket = Ticket {
+            id: 0, // assigned by the store
+            cid: ctx.user_id(),
+            title: ticket_fc.title,
+            status: TicketStatus::default(),
+            assignee: ticket_fc.assignee,
+            priority: ticket_fc.priority,
+            labels: normalize_labels(ticket_fc.labels),
+            due: ticket_fc.due,
+            ctime: now_utc(),
+            deleted_at: None,
+            deleted_by: None,
+            version: 1,
+        };
+        self.validate_ticket(&ticket).await?;
+
+        let ticket = self.store.insert_ticket(ticket).await?;
+        self.record_history(&ctx, HistoryAction::Created, None, Some(&ticket))
+            .await?;
+
+        Ok(ticket)
+    }
+
+    /// Lists a page of the tickets the ctx user can read
+    /// (the ones it created or is assigned to without `TicketReadAny`).
+    pub async fn list_tickets(
+        &self,
+        ctx: Ctx,
+        filter: TicketFilter,
+        options: TicketListOptions,
+    ) -> Result<TicketPage> {
+        let readable_by = (!ctx.has_permission(Permission::TicketReadAny)).then(|| ctx.user_id());
+        let mine = filter.mine;
+
+        let mut query = TicketListQuery::new(readable_by, ctx.user_id(), filter, options)?;
+        if mine {
+            // Others' tickets asked for, but only the own ones
+            if query.cid.is_some_and(|cid| cid != ctx.user_id()) {
+                return Ok(TicketPage {
+                    items: Vec::new(),
+                    next_cursor: None,
+                });
+            }
+            query.cid = Some(ctx.user_id());
+        }
+
+        self.store.list_tickets(query).await
+    }
+
+    pub async fn get_ticket(&self, ctx: Ctx, id: u64) -> Result<Ticket> {
+        let ticket = self
+            .store
+            .get_ticket(id)
+            .await?
+            .ok_or(Error::TicketNotFound { id })?;
+
+        check_ticket_access(&ctx, &ticket, TicketAccess::Read)?;
+
+        Ok(ticket)
+    }
+
+    /// With `expected_versions`, fails with `TicketVersionConflict` if the ticket is at none of them
+    /// (i.e., changed since).
+    pub async fn update_ticket(
+        &self,
+        ctx: Ctx,
+        id: u64,
+        ticket_fu: TicketForUpdate,
+        expected_versions: Option<Vec<u64>>,
+    ) -> Result<Ticket> {
+        let mut ticket = self
+            .store
+            .get_ticket(id)
+            .await?
+            .ok_or(Error::TicketNotFound { id })?;
+
+        check_ticket_access(&ctx, &ticket, TicketAccess::Update)?;
+        check_ticket_not_deleted(&ticket)?;
+        check_ticket_version(&ticket, expected_versions)?;
+        let before = ticket.clone();
+
+        let TicketForUpdate {
+            title,
+            assignee,
+            priority,
+            labels,
+            due,
+        } = ticket_fu;
+        if let Some(title) = title {
+            ticket.title = title;
+        }
+        if let Some(assignee) = assignee {
+            ticket.assignee = assignee;
+        }
+        if let Some(priority) = priority {
+            ticket.priority = priority;
+        }
+        if let Some(labels) = labels {
+            ticket.labels = normalize_labels(labels);
+        }
+        if let Some(due) = due {
+            ticket.due = due;
+        }
+        self.validate_ticket(&ticket).await?;
+
+        ticket.version += 1;
+        self.store.update_ticket(&ticket).await?;
+        self.record_history(&ctx, HistoryAction::Updated, Some(&before), Some(&ticket))
+            .await?;
+
+        Ok(ticket)
+    }
+
+    /// Move the ticket to another status, if allowed from its current one.
+    pub async fn transition_ticket(
+        &self,
+        ctx: Ctx,
+        id: u64,
+        ticket_ft: TicketForTransition,
+        expected_versions: Option<Vec<u64>>,
+    ) -> Result<Ticket> {
+        let mut ticket = self
+            .store
+            .get_ticket(id)
+            .await?
+            .ok_or(Error::TicketNotFound { id })?;
+
+        check_ticket_access(&ctx, &ticket, TicketAccess::Update)?;
+        check_ticket_not_deleted(&ticket)?;
+        check_ticket_version(&ticket, expected_versions)?;
+
+        let before = ticket.clone();
+        let (from, to) = (ticket.status, ticket_ft.status);
+        if !from.transitions().contains(&to) {
+            return Err(Error::InvalidTransition { from, to });
+        }
+        ticket.status = to;
+        ticket.version += 1;
+
+        self.store.update_ticket(&ticket).await?;
+        self.record_history(&ctx, HistoryAction::Updated, Some(&before), Some(&ticket))
+            .await?;
+
+        Ok(ticket)
+    }
+
+    /// Soft delete, the ticket can be restored until purged.
+    pub async fn delete_ticket(
+        &self,
+        ctx: Ctx,
+        id: u64,
+        expected_versions: Option<Vec<u64>>,
+    ) -> Result<Ticket> {
+        let mut ticket = self
+            .store
+            .get_ticket(id)
+            .await?
+            .ok_or(Error::TicketNotFound { id })?;
+
+        check_ticket_access(&ctx, &ticket, TicketAccess::Delete)?;
+        check_ticket_not_deleted(&ticket)?;
+        check_ticket_version(&ticket, expected_versions)?;
+
+        let before = ticket.clone();
+        ticket.deleted_at = Some(now_utc());
+        ticket.deleted_by = Some(ctx.user_id());
+        ticket.version += 1;
+
+        self.store.update_ticket(&ticket).await?;
+        self.record_history(&ctx, HistoryAction::Deleted, Some(&before), Some(&ticket))
+            .await?;
+
+        Ok(ticket)
+    }
+
+    /// Same access as for deleting it.
+    pub async fn restore_ticket(&self, ctx: Ctx, id: u64) -> Result<Ticket> {
+        let mut ticket = self
+            .store
+            .get_ticket(id)
+            .await?
+            .ok_or(Error::TicketNotFound { id })?;
+
+        check_ticket_access(&ctx, &ticket, TicketAccess::Delete)?;
+        if ticket.deleted_at.is_none() {
+            return Err(Error::TicketNotDeleted { id });
+        }
+
+        let before = ticket.clone();
+        ticket.deleted_at = None;
+        ticket.deleted_by = None;
+        ticket.version += 1;
+
+        self.store.update_ticket(&ticket).await?;
+        self.record_history(&ctx, HistoryAction::Restored, Some(&before), Some(&ticket))
+            .await?;
+
+        Ok(ticket)
+    }
+
+    /// Hard delete of a soft deleted ticket (the web layer requires `TicketPurge`).
+    /// Its history is kept.
+    pub async fn purge_ticket(&self, ctx: Ctx, id: u64) -> Result<Ticket> {
+        let ticket = self
+            .store
+            .get_ticket(id)
+            .await?
+            .ok_or(Error::TicketNotFound { id })?;
+
+        if ticket.deleted_at.is_none() {
+            return Err(Error::TicketNotDeleted { id });
+        }
+
+        // Restored (or purged) since, checked again by the store
+        let ticket = self
+            .store
+            .delete_ticket(id)
+            .await?
+            .ok_or(Error::TicketNotDeleted { id })?;
+        self.record_history(&ctx, HistoryAction::Purged, Some(&ticket), None)
+            .await?;
+
+        Ok(ticket)
+    }
+
+    /// Purge the tickets soft deleted for longer than the retention period.
+    /// A failed purge is logged, and does not stop the others.
+    async fn purge_expired_tickets(&self) -> Result<()> {
+        let cutoff = now_utc() - Duration::from_secs(config().ticket_retention_sec);
+        let ctx = service_ctx();
+
+        let expired = self.store.list_deleted_tickets(cutoff).await?;
+        for ticket in expired {
+            info!("{:<12} - purge_expired_tickets - {}", "PURGE", ticket.id);
+            if let Err(ex) = self.purge_ticket(ctx.clone(), ticket.id).await {
+                warn!(
+                    "{:<12} - purge_expired_tickets - {} - {ex:?}",
+                    "PURGE", ticket.id
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// For the changes made by the service itself (e.g., automatic purges),
+/// recorded with the user id 0.
+fn service_ctx() -> Ctx {
+    Ctx::new(0, vec![Role::Admin], None)
+}
+
+fn check_ticket_version(ticket: &Ticket, expected_versions: Option<Vec<u64>>) -> Result<()> {
+    match expected_versions {
+        Some(expected) if !expected.contains(&ticket.version) => {
+            Err(Error::TicketVersionConflict {
+                id: ticket.id,
+                expected,
+                actual: ticket.version,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_ticket_not_deleted(ticket: &Ticket) -> Result<()> {
+    match ticket.deleted_at {
+        None => Ok(()),
+        Some(_) => Err(Error::TicketDeleted { id: ticket.id }),
+    }
+}
+
+// History implementation
+impl ModelController {
+    /// The ticket history, oldest first, also for purged tickets.
+    pub async fn list_ticket_history(&self, ctx: Ctx, id: u64) -> Result<Vec<HistoryEntry>> {
+        let history = self.store.list_history(id).await?;
+
+        match self.store.get_ticket(id).await? {
+            Some(ticket) => check_ticket_access(&ctx, &ticket, TicketAccess::Read)?,
+
+            // Purged, its creator is the actor of its creation
+            None => {
+                let creator = history
+                    .iter()
+                    .find(|e| e.action == HistoryAction::Created)
+                    .map(|e| e.actor)
+                    .ok_or(Error::TicketNotFound { id })?;
+                if creator != ctx.user_id() && !ctx.has_permission(Permission::TicketReadAny) {
+                    return Err(Error::TicketAccessDenied { id });
+                }
+            }
+        }
+
+        Ok(history)
+    }
+
+    /// Record the ticket change, if any.
+    async fn record_history(
+        &self,
+        ctx: &Ctx,
+        action: HistoryAction,
+        before: Option<&Ticket>,
+        after: Option<&Ticket>,
+    ) -> Result<()> {
+        let changes = history::ticket_changes(before, after)?;
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let Some(ticket_id) = after.or(before).map(|t| t.id) else {
+            return Ok(());
+        };
+
+        let entry = HistoryEntry {
+            id: 0, // assigned by the store
+            ticket_id,
+            actor: ctx.user_id(),
+            time: now_utc(),
+            action,
+            changes,
+        };
+        self.store.insert_history(entry).await?;
+
+        Ok(())
+    }
+}
+
+// Ticket validation
+impl ModelController {
+    /// Checks the ticket fields, the assignee against the user store.
+    async fn validate_ticket(&self, ticket: &Ticket) -> Result<()> {
+        let mut errors = Vec::new();
+
+        if let Some(assignee) = ticket.assignee
+            && self.get_user(assignee).await?.is_none()
+        {
+            errors.push(FieldError {
+                field: "assignee".into(),
+                message: format!("no user with id {assignee}"),
+            });
+        }
+
+        if ticket.labels.len() > LABELS_MAX {
+            errors.push(FieldError {
+                field: "labels".into(),
+                message: format!("at most {LABELS_MAX} labels"),
+            });
+        }
+        if let Some(label) = ticket
+            .labels
+            .iter()
+            .find(|l| l.chars().count() > LABEL_LEN_MAX)
+        {
+            errors.push(FieldError {
+                field: "labels".into(),
+                message: format!("'{label}' is longer than {LABEL_LEN_MAX} characters"),
+            });
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::TicketFailValidation { errors })
+        }
+    }
+}
+
+/// Declarative rule (`custom(function = "validate_not_blank")`) for the required texts.
+fn validate_not_blank(text: &str) -> core::result::Result<(), ValidationError> {
+    if text.trim().is_empty() {
+        let mut error = ValidationError::new("not_blank");
+        error.message = Some("must not be empty".into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+/// Labels are trimmed, and the empty ones dropped.
+fn normalize_labels(labels: BTreeSet<String>) -> BTreeSet<String> {
+    labels
+        .into_iter()
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .collect()
+}
+
+/// What is done on a ticket, for the access checks.
+#[derive(Clone, Copy)]
+enum TicketAccess {
+    Read,
+    Update,
+    Delete, // also restore
+}
+
+impl TicketAccess {
+    /// On the own tickets, only a scoped ctx (i.e., an API key) can lack it.
+    fn own_permission(self) -> Option<Permission> {
+        match self {
+            TicketAccess::Read => None,
+            TicketAccess::Update => Some(Permission::TicketUpdateOwn),
+            TicketAccess::Delete => Some(Permission::TicketDeleteOwn),
+        }
+    }
+
+    /// Whether the assignee has it as well as the creator.
+    fn by_assignee(self) -> bool {
+        !matches!(self, TicketAccess::Delete)
+    }
+
+    /// On others' tickets.
+    fn any_permission(self) -> Permission {
+        match self {
+            TicketAccess::Read => Permission::TicketReadAny,
+            TicketAccess::Update => Permission::TicketUpdateAny,
+            TicketAccess::Delete => Permission::TicketDeleteAny,
+        }
+    }
+}
+
+/// Users can access their own tickets, and read and update the ones assigned to them
+/// (within their scopes), others' ones need the `any` permission of the access.
+fn check_ticket_access(ctx: &Ctx, ticket: &Ticket, access: TicketAccess) -> Result<()> {
+    let user_id = ctx.user_id();
+    let is_own = (ticket.cid == user_id
+        || (access.by_assignee() && ticket.assignee == Some(user_id)))
+        && access
+            .own_permission()
+            .is_none_or(|permission| ctx.in_scopes(permission));
+
+    if is_own || ctx.has_permission(access.any_permission()) {
+        Ok(())
+    } else {
+        Err(Error::TicketAccessDenied { id: ticket.id })
+    }
+}
+
+// Comment implementation
+impl ModelController {
+    /// Anyone who can read the ticket can comment on it.
+    pub async fn create_comment(
+        &self,
+        ctx: Ctx,
+        ticket_id: u64,
+        comment_fc: CommentForCreate,
+    ) -> Result<Comment> {
+        check_in_scopes(&ctx, Permission::CommentWrite)?;
+        let ticket = self.get_ticket(ctx.clone(), ticket_id).await?;
+        check_ticket_not_deleted(&ticket)?;
+
+        let comment = Comment {
+            id: 0, // assigned by the store
+            ticket_id,
+            author: ctx.user_id(),
+            body: comment_fc.body,
+            ctime: now_utc(),
+            mtime: None,
+        };
+
+        self.store.insert_comment(comment).await
+    }
+
+    pub async fn list_comments(&self, ctx: Ctx, ticket_id: u64) -> Result<Vec<Comment>> {
+        self.get_ticket(ctx, ticket_id).await?;
+
+        self.store.list_comments(ticket_id).await
+    }
+
+    /// Only the comment author can edit it.
+    pub async fn update_comment(
+        &self,
+        ctx: Ctx,
+        ticket_id: u64,
+        id: u64,
+        comment_fu: CommentForUpdate,
+    ) -> Result<Comment> {
+        let mut comment = self.get_author_comment(ctx, ticket_id, id).await?;
+
+        comment.body = comment_fu.body;
+        comment.mtime = Some(now_utc());
+
+        self.store.update_comment(&comment).await?;
+
+        Ok(comment)
+    }
+
+    /// Only the comment author can delete it.
+    pub async fn delete_comment(&self, ctx: Ctx, ticket_id: u64, id: u64) -> Result<Comment> {
+        self.get_author_comment(ctx, ticket_id, id).await?;
+
+        self.store
+            .delete_comment(id)
+            .await?
+            .ok_or(Error::CommentNotFound { id })
+    }
+
+    /// The comment of the ticket, if the ctx user can read the ticket and wrote the comment.
+    async fn get_author_comment(&self, ctx: Ctx, ticket_id: u64, id: u64) -> Result<Comment> {
+        check_in_scopes(&ctx, Permission::CommentWrite)?;
+        let ticket = self.get_ticket(ctx.clone(), ticket_id).await?;
+        check_ticket_not_deleted(&ticket)?;
+
+        let comment = self
+            .store
+            .get_comment(id)
+            .await?
+            .filter(|c| c.ticket_id == ticket_id)
+            .ok_or(Error::CommentNotFound { id })?;
+
+        if comment.author != ctx.user_id() {
+            return Err(Error::CommentAccessDenied { id });
+        }
+
+        Ok(comment)
+    }
+}
+
+// User implementation
+impl ModelController {
+    pub async fn create_user(&self, user_fc: UserForCreate) -> Result<User> {
+        let user = User {
+            id: 0, // assigned by the store
+            username: user_fc.username,
+            pwd: hash_pwd(user_fc.pwd_clear).await?,
+            token_salt: Uuid::new_v4(),
+            roles: user_fc.roles,
+            ctime: now_utc(),
+        };
+
+        self.store.insert_user(user).await
+    }
+
+    pub async fn get_user(&self, id: u64) -> Result<Option<User>> {
+        self.store.get_user(id).await
+    }
+
+    pub async fn update_user_roles(&self, id: u64, roles: Vec<Role>) -> Result<User> {
+        self.store.update_user_roles(id, &roles).await?;
+
+        self.get_user(id).await?.ok_or(Error::UserNotFound { id })
+    }
+
+    pub async fn first_user_by_username(&self, username: &str) -> Result<Option<User>> {
+        self.store.first_user_by_username(username).await
+    }
+}
+
+// ApiKey implementation
+impl ModelController {
+    /// Returns the api key with its clear string, the only time it is available.
+    /// From a scoped ctx (i.e., an API key), the new key cannot exceed its scopes
+    /// (and gets them when none are given).
+    pub async fn create_api_key(
+        &self,
+        ctx: Ctx,
+        api_key_fc: ApiKeyForCreate,
+    ) -> Result<(ApiKey, ApiKeyToken)> {
+        check_in_scopes(&ctx, Permission::CredentialManage)?;
+
+        let scopes = match (ctx.scopes(), api_key_fc.scopes) {
+            (Some(ctx_scopes), None) => Some(ctx_scopes.to_vec()),
+            (_, scopes) => scopes,
+        };
+        if let Some(permission) = scopes
+            .iter()
+            .flatten()
+            .find(|permission| !ctx.in_scopes(**permission))
+        {
+            return Err(Error::AuthFailNoPermission {
+                permission: *permission,
+            });
+        }
+
+        let (key, key_hash) = generate_api_key()?;
+
+        let api_key = ApiKey {
+            id: key.id,
+            user_id: ctx.user_id(),
+            name: api_key_fc.name,
+            key_hash,
+            scopes,
+            exp: api_key_fc.exp,
+            ctime: now_utc(),
+        };
+
+        let api_key = self.store.insert_api_key(api_key).await?;
+
+        Ok((api_key, key))
+    }
+
+    /// Lists the ctx user api keys.
+    pub async fn list_api_keys(&self, ctx: Ctx) -> Result<Vec<ApiKey>> {
+        check_in_scopes(&ctx, Permission::CredentialManage)?;
+
+        self.store.list_api_keys(ctx.user_id()).await
+    }
+
+    /// Revokes one of the ctx user api keys.
+    pub async fn delete_api_key(&self, ctx: Ctx, id: Uuid) -> Result<ApiKey> {
+        check_in_scopes(&ctx, Permission::CredentialManage)?;
+
+        // Others' keys are reported as not found, not to disclose them
+        if self
+            .store
+            .get_api_key(id)
+            .await?
+            .is_none_or(|k| k.user_id != ctx.user_id())
+        {
+            return Err(Error::ApiKeyNotFound { id });
+        }
+
+        self.store
+            .delete_api_key(id)
+            .await?
+            .ok_or(Error::ApiKeyNotFound { id })
+    }
+
+    /// For authentication only (no ctx yet).
+    pub async fn get_api_key(&self, id: Uuid) -> Result<Option<ApiKey>> {
+        self.store.get_api_key(id).await
+    }
+}
+
+/// For the permissions on the user own resources (e.g., `CredentialManage`),
+/// which only a scoped ctx (i.e., an API key) can lack.
+pub fn check_in_scopes(ctx: &Ctx, permission: Permission) -> Result<()> {
+    if ctx.in_scopes(permission) {
+        Ok(())
+    } else {
+        Err(Error::AuthFailNoPermission { permission })
+    }
+}
+
+// Auth implementation
+impl ModelController {
+    pub async fn user_token_salt(&self, user_id: u64) -> Result<Uuid> {
+        self.get_user(user_id)
+            .await?
+            .map(|user| user.token_salt)
+            .ok_or(Error::AuthFailUnknownUser { user_id })
+    }
+
+    /// Replace the user token salt, which invalidates all of the user tokens
+    /// (i.e., logs out all of the user sessions).
+    pub async fn reset_user_token_salt(&self, user_id: u64) -> Result<Uuid> {
+        let token_salt = Uuid::new_v4();
+
+        self.store
+            .update_user_token_salt(user_id, token_salt)
+            .await
+            .map_err(|ex| match ex {
+                Error::UserNotFound { .. } => Error::AuthFailUnknownUser { user_id },
+                ex => ex,
+            })?;
+
+        Ok(token_salt)
+    }
+
+    /// Revoke a session (all tokens sharing the same origin) until its max lifetime,
+    /// after which its tokens are expired anyway (even the renewed ones).
+    pub async fn revoke_session(&self, user_id: u64, origin: u64) -> Result<()> {
+        let until = origin + config().session_max_sec;
+
+        self.store
+            .revoke_session(user_id, origin, until, now_utc_sec())
+            .await
+    }
+
+    pub async fn is_session_revoked(&self, user_id: u64, origin: u64) -> Result<bool> {
+        self.store
+            .is_session_revoked(user_id, origin, now_utc_sec())
+            .await
+    }
+}
+
+// endregion: --- Model Controller
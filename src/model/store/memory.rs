@@ -0,0 +1,356 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use time::OffsetDateTime;
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::ctx::Role;
+use crate::model::list::{self, TicketListQuery};
+use crate::model::store::{ApiKeyStore, CommentStore, HistoryStore, TicketStore, UserStore};
+use crate::model::{ApiKey, Comment, HistoryEntry, Ticket, TicketPage, User};
+use crate::{Error, Result};
+
+const COMPACTION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// In memory store (everything is lost on restart).
+#[derive(Default)]
+pub struct MemoryStore {
+    tickets: Mutex<TicketTable>,
+    comments: Mutex<CommentTable>,
+    history: Mutex<Vec<HistoryEntry>>, // entry id is index + 1
+    users: Mutex<Vec<User>>,           // user id is index + 1
+    revoked_sessions: Mutex<HashMap<(u64, u64), u64>>, // (user_id, origin) -> until
+    api_keys: Mutex<HashMap<Uuid, ApiKey>>,
+}
+
+#[derive(Default)]
+struct TicketTable {
+    last_id: u64, // ids are never reused, even after deletes
+    tickets: HashMap<u64, Ticket>,
+}
+
+#[derive(Default)]
+struct CommentTable {
+    last_id: u64,
+    comments: BTreeMap<u64, Comment>, // by id, so in creation order
+}
+
+// Constructor
+impl MemoryStore {
+    /// Also starts the background compaction, which stops when the store is dropped.
+    pub fn new() -> Arc<Self> {
+        let store = Arc::new(Self::default());
+
+        let weak_store = Arc::downgrade(&store);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(COMPACTION_INTERVAL);
+            loop {
+                interval.tick().await;
+                let Some(store) = weak_store.upgrade() else {
+                    break;
+                };
+                store.compact_tickets();
+            }
+        });
+
+        store
+    }
+}
+
+impl MemoryStore {
+    /// Deleted tickets are removed right away, but the map keeps its capacity,
+    /// so give it back when mostly unused.
+    fn compact_tickets(&self) {
+        let mut table = self.tickets.lock().unwrap();
+
+        let (len, capacity) = (table.tickets.len(), table.tickets.capacity());
+        if capacity > 64 && capacity > len * 4 {
+            table.tickets.shrink_to_fit();
+            debug!(
+                "{:<12} - compact_tickets - capacity {capacity} -> {}",
+                "STORE",
+                table.tickets.capacity()
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl TicketStore for MemoryStore {
+    async fn insert_ticket(&self, mut ticket: Ticket) -> Result<Ticket> {
+        let mut table = self.tickets.lock().unwrap();
+
+        table.last_id += 1;
+        ticket.id = table.last_id;
+        table.tickets.insert(ticket.id, ticket.clone());
+
+        Ok(ticket)
+    }
+
+    async fn get_ticket(&self, id: u64) -> Result<Option<Ticket>> {
+        let table = self.tickets.lock().unwrap();
+
+        Ok(table.tickets.get(&id).cloned())
+    }
+
+    async fn list_tickets(&self, query: TicketListQuery) -> Result<TicketPage> {
+        // Lock is exclusive anyway
+        let table = self.tickets.lock().unwrap();
+
+        let tickets: Vec<Ticket> = table
+            .tickets
+            .values()
+            .filter(|t| query.matches(t))
+            .cloned()
+            .collect();
+
+        list::paginate(tickets, &query)
+    }
+
+    async fn list_deleted_tickets(&self, deleted_before: OffsetDateTime) -> Result<Vec<Ticket>> {
+        let table = self.tickets.lock().unwrap();
+
+        let mut tickets: Vec<Ticket> = table
+            .tickets
+            .values()
+            .filter(|t| {
+                t.deleted_at
+                    .is_some_and(|deleted_at| deleted_at < deleted_before)
+            })
+            .cloned()
+            .collect();
+        tickets.sort_by_key(|t| t.id);
+
+        Ok(tickets)
+    }
+
+    async fn update_ticket(&self, ticket: &Ticket) -> Result<()> {
+        let mut table = self.tickets.lock().unwrap();
+
+        let stored = table
+            .tickets
+            .get_mut(&ticket.id)
+            .ok_or(Error::TicketNotFound { id: ticket.id })?;
+        if stored.version + 1 != ticket.version {
+            return Err(Error::TicketVersionConflict {
+                id: ticket.id,
+                expected: vec![ticket.version - 1],
+                actual: stored.version,
+            });
+        }
+        *stored = ticket.clone();
+
+        Ok(())
+    }
+
+    async fn delete_ticket(&self, id: u64) -> Result<Option<Ticket>> {
+        let mut table = self.tickets.lock().unwrap();
+
+        if table
+            .tickets
+            .get(&id)
+            .is_none_or(|t| t.deleted_at.is_none())
+        {
+            return Ok(None);
+        }
+
+        let ticket = table.tickets.remove(&id);
+        if ticket.is_some() {
+            let mut comment_table = self.comments.lock().unwrap();
+            comment_table.comments.retain(|_, c| c.ticket_id != id);
+        }
+
+        Ok(ticket)
+    }
+}
+
+#[async_trait]
+impl CommentStore for MemoryStore {
+    async fn insert_comment(&self, mut comment: Comment) -> Result<Comment> {
+        let mut table = self.comments.lock().unwrap();
+
+        table.last_id += 1;
+        comment.id = table.last_id;
+        table.comments.insert(comment.id, comment.clone());
+
+        Ok(comment)
+    }
+
+    async fn get_comment(&self, id: u64) -> Result<Option<Comment>> {
+        let table = self.comments.lock().unwrap();
+
+        Ok(table.comments.get(&id).cloned())
+    }
+
+    async fn list_comments(&self, ticket_id: u64) -> Result<Vec<Comment>> {
+        let table = self.comments.lock().unwrap();
+
+        Ok(table
+            .comments
+            .values()
+            .filter(|c| c.ticket_id == ticket_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn update_comment(&self, comment: &Comment) -> Result<()> {
+        let mut table = self.comments.lock().unwrap();
+
+        let stored = table
+            .comments
+            .get_mut(&comment.id)
+            .ok_or(Error::CommentNotFound { id: comment.id })?;
+        *stored = comment.clone();
+
+        Ok(())
+    }
+
+    async fn delete_comment(&self, id: u64) -> Result<Option<Comment>> {
+        let mut table = self.comments.lock().unwrap();
+
+        Ok(table.comments.remove(&id))
+    }
+}
+
+#[async_trait]
+impl HistoryStore for MemoryStore {
+    async fn insert_history(&self, mut entry: HistoryEntry) -> Result<HistoryEntry> {
+        let mut store = self.history.lock().unwrap();
+
+        entry.id = store.len() as u64 + 1;
+        store.push(entry.clone());
+
+        Ok(entry)
+    }
+
+    async fn list_history(&self, ticket_id: u64) -> Result<Vec<HistoryEntry>> {
+        let store = self.history.lock().unwrap();
+
+        Ok(store
+            .iter()
+            .filter(|e| e.ticket_id == ticket_id)
+            .cloned()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl UserStore for MemoryStore {
+    async fn insert_user(&self, mut user: User) -> Result<User> {
+        let mut store = self.users.lock().unwrap();
+
+        // Checked under the lock, so two registrations cannot race each other
+        if store
+            .iter()
+            .any(|u| u.username.eq_ignore_ascii_case(&user.username))
+        {
+            return Err(Error::UserUsernameAlreadyExists {
+                username: user.username,
+            });
+        }
+
+        user.id = store.len() as u64 + 1;
+        store.push(user.clone());
+
+        Ok(user)
+    }
+
+    async fn get_user(&self, id: u64) -> Result<Option<User>> {
+        let store = self.users.lock().unwrap();
+
+        Ok(id
+            .checked_sub(1)
+            .and_then(|idx| store.get(idx as usize))
+            .cloned())
+    }
+
+    async fn first_user_by_username(&self, username: &str) -> Result<Option<User>> {
+        let store = self.users.lock().unwrap();
+
+        Ok(store.iter().find(|u| u.username == username).cloned())
+    }
+
+    async fn count_users(&self) -> Result<u64> {
+        Ok(self.users.lock().unwrap().len() as u64)
+    }
+
+    async fn update_user_roles(&self, id: u64, roles: &[Role]) -> Result<()> {
+        let mut store = self.users.lock().unwrap();
+
+        let user = id
+            .checked_sub(1)
+            .and_then(|idx| store.get_mut(idx as usize))
+            .ok_or(Error::UserNotFound { id })?;
+        user.roles = roles.to_vec();
+
+        Ok(())
+    }
+
+    async fn update_user_token_salt(&self, id: u64, token_salt: Uuid) -> Result<()> {
+        let mut store = self.users.lock().unwrap();
+
+        let user = id
+            .checked_sub(1)
+            .and_then(|idx| store.get_mut(idx as usize))
+            .ok_or(Error::UserNotFound { id })?;
+        user.token_salt = token_salt;
+
+        Ok(())
+    }
+
+    async fn revoke_session(&self, user_id: u64, origin: u64, until: u64, now: u64) -> Result<()> {
+        let mut revoked = self.revoked_sessions.lock().unwrap();
+
+        revoked.retain(|_, until| *until > now);
+        revoked.insert((user_id, origin), until);
+
+        Ok(())
+    }
+
+    async fn is_session_revoked(&self, user_id: u64, origin: u64, now: u64) -> Result<bool> {
+        let revoked = self.revoked_sessions.lock().unwrap();
+
+        Ok(revoked
+            .get(&(user_id, origin))
+            .is_some_and(|until| *until > now))
+    }
+}
+
+#[async_trait]
+impl ApiKeyStore for MemoryStore {
+    async fn insert_api_key(&self, api_key: ApiKey) -> Result<ApiKey> {
+        let mut store = self.api_keys.lock().unwrap();
+
+        store.insert(api_key.id, api_key.clone());
+
+        Ok(api_key)
+    }
+
+    async fn get_api_key(&self, id: Uuid) -> Result<Option<ApiKey>> {
+        let store = self.api_keys.lock().unwrap();
+
+        Ok(store.get(&id).cloned())
+    }
+
+    async fn list_api_keys(&self, user_id: u64) -> Result<Vec<ApiKey>> {
+        let store = self.api_keys.lock().unwrap();
+
+        let mut api_keys: Vec<ApiKey> = store
+            .values()
+            .filter(|k| k.user_id == user_id)
+            .cloned()
+            .collect();
+        api_keys.sort_by_key(|k| k.ctime);
+
+        Ok(api_keys)
+    }
+
+    async fn delete_api_key(&self, id: Uuid) -> Result<Option<ApiKey>> {
+        let mut store = self.api_keys.lock().unwrap();
+
+        Ok(store.remove(&id))
+    }
+}
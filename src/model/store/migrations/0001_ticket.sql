@@ -0,0 +1,6 @@
+-- AUTOINCREMENT, so ids of deleted tickets are never reused
+CREATE TABLE ticket (
+    id    INTEGER PRIMARY KEY AUTOINCREMENT,
+    cid   INTEGER NOT NULL,
+    title TEXT NOT NULL
+);
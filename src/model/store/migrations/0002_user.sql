@@ -0,0 +1,19 @@
+CREATE TABLE user (
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    username   TEXT NOT NULL,
+    pwd        TEXT NOT NULL, -- argon2 PHC string
+    token_salt TEXT NOT NULL,
+    roles      TEXT NOT NULL, -- JSON array, e.g., ["admin"]
+    ctime      TEXT NOT NULL
+);
+
+-- Usernames are unique, case insensitive
+CREATE UNIQUE INDEX user_username_nocase ON user (username COLLATE NOCASE);
+
+-- (user_id, origin) sessions logged out, until their tokens are expired anyway
+CREATE TABLE revoked_session (
+    user_id INTEGER NOT NULL,
+    origin  INTEGER NOT NULL,
+    until   INTEGER NOT NULL,
+    PRIMARY KEY (user_id, origin)
+);
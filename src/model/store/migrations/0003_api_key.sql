@@ -0,0 +1,12 @@
+-- API keys, only the HMAC of their secret is stored
+CREATE TABLE api_key (
+    id       BLOB PRIMARY KEY, -- uuid, the key prefix
+    user_id  INTEGER NOT NULL,
+    name     TEXT NOT NULL,
+    key_hash TEXT NOT NULL,
+    scopes   TEXT,             -- JSON array, e.g., ["TicketReadAny"], NULL for all
+    exp      TEXT,             -- NULL for no expiration
+    ctime    TEXT NOT NULL
+);
+
+CREATE INDEX api_key_user_id ON api_key (user_id);
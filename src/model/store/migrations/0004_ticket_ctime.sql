@@ -0,0 +1,2 @@
+-- Tickets created before have no known creation time
+ALTER TABLE ticket ADD COLUMN ctime TEXT NOT NULL DEFAULT '1970-01-01T00:00:00Z';
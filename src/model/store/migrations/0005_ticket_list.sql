@@ -0,0 +1,4 @@
+-- For the ticket list pages, in the order of their sorts (the id breaking the ties)
+CREATE INDEX ticket_cid ON ticket (cid, id);
+CREATE INDEX ticket_title ON ticket (title, id);
+CREATE INDEX ticket_ctime ON ticket (strftime('%Y-%m-%dT%H:%M:%f', ctime), id);
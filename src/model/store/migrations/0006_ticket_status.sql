@@ -0,0 +1,2 @@
+-- open | in_progress | blocked | resolved | closed
+ALTER TABLE ticket ADD COLUMN status TEXT NOT NULL DEFAULT 'open';
@@ -0,0 +1,4 @@
+ALTER TABLE ticket ADD COLUMN assignee INTEGER;                          -- user id
+ALTER TABLE ticket ADD COLUMN priority TEXT NOT NULL DEFAULT 'medium'; -- low | medium | high | urgent
+ALTER TABLE ticket ADD COLUMN labels TEXT NOT NULL DEFAULT '[]';       -- JSON array, e.g., ["bug"]
+ALTER TABLE ticket ADD COLUMN due TEXT;
@@ -0,0 +1,10 @@
+CREATE TABLE comment (
+    id        INTEGER PRIMARY KEY AUTOINCREMENT,
+    ticket_id INTEGER NOT NULL,
+    author    INTEGER NOT NULL, -- user id
+    body      TEXT NOT NULL,
+    ctime     TEXT NOT NULL,
+    mtime     TEXT              -- last edit, if any
+);
+
+CREATE INDEX comment_ticket_id ON comment (ticket_id);
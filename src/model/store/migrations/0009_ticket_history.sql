@@ -0,0 +1,11 @@
+-- Append only, kept when the ticket is deleted
+CREATE TABLE ticket_history (
+    id        INTEGER PRIMARY KEY AUTOINCREMENT,
+    ticket_id INTEGER NOT NULL,
+    actor     INTEGER NOT NULL, -- user id
+    time      TEXT NOT NULL,
+    action    TEXT NOT NULL,    -- created | updated | deleted
+    changes   TEXT NOT NULL     -- JSON array of {field, before, after}
+);
+
+CREATE INDEX ticket_history_ticket_id ON ticket_history (ticket_id);
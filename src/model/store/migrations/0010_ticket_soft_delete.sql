@@ -0,0 +1,3 @@
+-- Soft deleted tickets, purged after the retention period
+ALTER TABLE ticket ADD COLUMN deleted_at TEXT;
+ALTER TABLE ticket ADD COLUMN deleted_by INTEGER; -- user id
@@ -0,0 +1,2 @@
+-- Incremented on every change, for optimistic concurrency
+ALTER TABLE ticket ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
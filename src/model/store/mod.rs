@@ -0,0 +1,144 @@
+//! Storage backends for the ModelController.
+//! The ModelController holds the business logic (e.g., access checks),
+//! the stores only persist the entities.
+
+mod memory;
+mod sqlite;
+#[cfg(test)]
+mod tests;
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::ctx::Role;
+use crate::model::list::TicketListQuery;
+use crate::model::{ApiKey, Comment, HistoryEntry, Ticket, TicketPage, User};
+use crate::{Error, Result};
+
+pub use self::memory::MemoryStore;
+pub use self::sqlite::SqliteStore;
+
+#[async_trait]
+pub trait TicketStore: Send + Sync {
+    /// Insert a new ticket, its `id` being assigned by the store.
+    async fn insert_ticket(&self, ticket: Ticket) -> Result<Ticket>;
+
+    async fn get_ticket(&self, id: u64) -> Result<Option<Ticket>>;
+
+    /// The page of the tickets matching the query, after its cursor
+    /// (see `list::paginate` for the reference behavior).
+    async fn list_tickets(&self, query: TicketListQuery) -> Result<TicketPage>;
+
+    /// List the tickets soft deleted before `deleted_before` by id (e.g., for the purge).
+    async fn list_deleted_tickets(&self, deleted_before: OffsetDateTime) -> Result<Vec<Ticket>>;
+
+    /// Replace the ticket of the same id, `TicketNotFound` if there is none.
+    /// Only if the stored one is the previous version (`ticket.version - 1`),
+    /// `TicketVersionConflict` otherwise, so concurrent updates cannot overwrite each other.
+    async fn update_ticket(&self, ticket: &Ticket) -> Result<()>;
+
+    /// Hard delete (i.e., purge), which also deletes the ticket comments.
+    /// Only if soft deleted, checked with the delete itself, `None` otherwise
+    /// (e.g., restored in between).
+    async fn delete_ticket(&self, id: u64) -> Result<Option<Ticket>>;
+}
+
+#[async_trait]
+pub trait CommentStore: Send + Sync {
+    /// Insert a new comment, its `id` being assigned by the store.
+    async fn insert_comment(&self, comment: Comment) -> Result<Comment>;
+
+    async fn get_comment(&self, id: u64) -> Result<Option<Comment>>;
+
+    /// List the ticket comments by id (i.e., oldest first).
+    async fn list_comments(&self, ticket_id: u64) -> Result<Vec<Comment>>;
+
+    /// Replace the comment of the same id, `CommentNotFound` if there is none.
+    async fn update_comment(&self, comment: &Comment) -> Result<()>;
+
+    async fn delete_comment(&self, id: u64) -> Result<Option<Comment>>;
+}
+
+/// Append only, the history is kept when the ticket is deleted.
+#[async_trait]
+pub trait HistoryStore: Send + Sync {
+    /// Insert a new entry, its `id` being assigned by the store.
+    async fn insert_history(&self, entry: HistoryEntry) -> Result<HistoryEntry>;
+
+    /// List the ticket entries by id (i.e., oldest first).
+    async fn list_history(&self, ticket_id: u64) -> Result<Vec<HistoryEntry>>;
+}
+
+#[async_trait]
+pub trait UserStore: Send + Sync {
+    /// Insert a new user, its `id` being assigned by the store.
+    /// `UserUsernameAlreadyExists` if the username is taken (case insensitive).
+    async fn insert_user(&self, user: User) -> Result<User>;
+
+    async fn get_user(&self, id: u64) -> Result<Option<User>>;
+
+    async fn first_user_by_username(&self, username: &str) -> Result<Option<User>>;
+
+    async fn count_users(&self) -> Result<u64>;
+
+    /// `UserNotFound` if there is no user of this id.
+    async fn update_user_roles(&self, id: u64, roles: &[Role]) -> Result<()>;
+
+    /// `UserNotFound` if there is no user of this id.
+    async fn update_user_token_salt(&self, id: u64, token_salt: Uuid) -> Result<()>;
+
+    /// Also drops the revocations expired at `now`.
+    async fn revoke_session(&self, user_id: u64, origin: u64, until: u64, now: u64) -> Result<()>;
+
+    async fn is_session_revoked(&self, user_id: u64, origin: u64, now: u64) -> Result<bool>;
+}
+
+#[async_trait]
+pub trait ApiKeyStore: Send + Sync {
+    /// Insert a new api key, its `id` (random) being part of the key itself.
+    async fn insert_api_key(&self, api_key: ApiKey) -> Result<ApiKey>;
+
+    async fn get_api_key(&self, id: Uuid) -> Result<Option<ApiKey>>;
+
+    /// List the user api keys by creation time.
+    async fn list_api_keys(&self, user_id: u64) -> Result<Vec<ApiKey>>;
+
+    async fn delete_api_key(&self, id: Uuid) -> Result<Option<ApiKey>>;
+}
+
+/// All of the entities the ModelController persists.
+pub trait Store: TicketStore + CommentStore + HistoryStore + UserStore + ApiKeyStore {}
+
+impl<T: TicketStore + CommentStore + HistoryStore + UserStore + ApiKeyStore> Store for T {}
+
+// region: --- Store Selection
+#[derive(Clone, Copy, Debug)]
+pub enum StoreKind {
+    Memory,
+    Sqlite,
+}
+
+impl FromStr for StoreKind {
+    type Err = Error;
+
+    fn from_str(kind: &str) -> Result<Self> {
+        match kind {
+            "memory" => Ok(Self::Memory),
+            "sqlite" => Ok(Self::Sqlite),
+            _ => Err(Error::ConfigWrongFormat("SERVICE_STORE")),
+        }
+    }
+}
+
+/// For `Sqlite`, `db_path` is the database file (`:memory:` for an in memory database).
+pub fn new_store(kind: StoreKind, db_path: &str) -> Result<Arc<dyn Store>> {
+    Ok(match kind {
+        StoreKind::Memory => MemoryStore::new(),
+        StoreKind::Sqlite => Arc::new(SqliteStore::open(db_path)?),
+    })
+}
+// endregion: --- Store Selection
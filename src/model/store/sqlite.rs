@@ -0,0 +1,904 @@
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef};
+use rusqlite::{Connection, ErrorCode, OptionalExtension, Row, ToSql, params};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::ctx::Role;
+use crate::model::list::{self, SortOrder, TicketListQuery, TicketSort};
+use crate::model::store::{ApiKeyStore, CommentStore, HistoryStore, TicketStore, UserStore};
+use crate::model::{
+    ApiKey, Comment, HistoryAction, HistoryEntry, Ticket, TicketPage, TicketPriority, TicketStatus,
+    User,
+};
+use crate::utils::now_utc;
+use crate::{Error, Result};
+
+/// Schema migrations, applied in order and recorded in the `_migration` table.
+/// Never edit a released one, add a new one instead.
+const MIGRATIONS: &[(u32, &str, &str)] = &[
+    (1, "ticket", include_str!("migrations/0001_ticket.sql")),
+    (2, "user", include_str!("migrations/0002_user.sql")),
+    (3, "api_key", include_str!("migrations/0003_api_key.sql")),
+    (
+        4,
+        "ticket_ctime",
+        include_str!("migrations/0004_ticket_ctime.sql"),
+    ),
+    (
+        5,
+        "ticket_list",
+        include_str!("migrations/0005_ticket_list.sql"),
+    ),
+    (
+        6,
+        "ticket_status",
+        include_str!("migrations/0006_ticket_status.sql"),
+    ),
+    (
+        7,
+        "ticket_triage",
+        include_str!("migrations/0007_ticket_triage.sql"),
+    ),
+    (8, "comment", include_str!("migrations/0008_comment.sql")),
+    (
+        9,
+        "ticket_history",
+        include_str!("migrations/0009_ticket_history.sql"),
+    ),
+    (
+        10,
+        "ticket_soft_delete",
+        include_str!("migrations/0010_ticket_soft_delete.sql"),
+    ),
+    (
+        11,
+        "ticket_version",
+        include_str!("migrations/0011_ticket_version.sql"),
+    ),
+];
+
+const TICKET_COLUMNS: &str = "id, cid, title, status, assignee, priority, labels, due, ctime, deleted_at, deleted_by, version";
+
+/// Sortable ctime (RFC 3339 texts are not, e.g., with trimmed fractional seconds),
+/// to the millisecond, the id breaking the ties.
+const TICKET_CTIME_KEY: &str = "strftime('%Y-%m-%dT%H:%M:%f', ctime)";
+
+const COMMENT_COLUMNS: &str = "id, ticket_id, author, body, ctime, mtime";
+
+const HISTORY_COLUMNS: &str = "id, ticket_id, actor, time, action, changes";
+
+const USER_COLUMNS: &str = "id, username, pwd, token_salt, roles, ctime";
+
+const API_KEY_COLUMNS: &str = "id, user_id, name, key_hash, scopes, exp, ctime";
+
+/// Embedded SQL store.
+/// Queries are blocking (file I/O, and the connection lock), so they run
+/// off the async runtime threads (see `with_conn`).
+pub struct SqliteStore {
+    conn: Arc<Mutex<Connection>>,
+}
+
+// Constructor
+impl SqliteStore {
+    /// Open (or create) the database, and apply the pending migrations.
+    pub fn open(db_path: &str) -> Result<Self> {
+        let mut conn = if db_path == ":memory:" {
+            Connection::open_in_memory()
+        } else {
+            if let Some(dir) = Path::new(db_path).parent() {
+                fs::create_dir_all(dir).map_err(|ex| Error::StoreFail(ex.to_string()))?;
+            }
+            Connection::open(db_path)
+        }
+        .map_err(sqlite_error)?;
+
+        migrate(&mut conn)?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+}
+
+impl SqliteStore {
+    /// Run the queries on the blocking thread pool, as for the password hashing.
+    async fn with_conn<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+
+        tokio::task::spawn_blocking(move || f(&mut conn.lock().unwrap()))
+            .await
+            .map_err(|ex| Error::StoreFail(ex.to_string()))?
+    }
+}
+
+fn migrate(conn: &mut Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS _migration (
+            version    INTEGER PRIMARY KEY,
+            name       TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        );",
+    )
+    .map_err(sqlite_error)?;
+
+    let current: u32 = conn
+        .query_row(
+            "SELECT COALESCE(MAX(version), 0) FROM _migration",
+            [],
+            |r| r.get(0),
+        )
+        .map_err(sqlite_error)?;
+
+    for (version, name, sql) in MIGRATIONS.iter().filter(|(v, ..)| *v > current) {
+        info!("{:<12} - {version:04}_{name}", "MIGRATION");
+
+        // One transaction per migration, so a failed one is not recorded
+        let tx = conn.transaction().map_err(sqlite_error)?;
+        tx.execute_batch(sql).map_err(sqlite_error)?;
+        tx.execute(
+            "INSERT INTO _migration (version, name, applied_at) VALUES (?1, ?2, ?3)",
+            params![version, name, format_time(now_utc())?],
+        )
+        .map_err(sqlite_error)?;
+        tx.commit().map_err(sqlite_error)?;
+    }
+
+    Ok(())
+}
+
+#[async_trait]
+impl TicketStore for SqliteStore {
+    async fn insert_ticket(&self, mut ticket: Ticket) -> Result<Ticket> {
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT INTO ticket (cid, title, status, assignee, priority, labels, due, ctime,
+                        deleted_at, deleted_by, version)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    ticket.cid,
+                    ticket.title,
+                    ticket.status,
+                    ticket.assignee,
+                    ticket.priority,
+                    to_json(&ticket.labels)?,
+                    ticket.due.map(format_time).transpose()?,
+                    format_time(ticket.ctime)?,
+                    ticket.deleted_at.map(format_time).transpose()?,
+                    ticket.deleted_by,
+                    ticket.version
+                ],
+            )
+            .map_err(sqlite_error)?;
+            ticket.id = conn.last_insert_rowid() as u64;
+
+            Ok(ticket)
+        })
+        .await
+    }
+
+    async fn get_ticket(&self, id: u64) -> Result<Option<Ticket>> {
+        // Not a rowid, so of no row (e.g., an id from a client)
+        let Some(id) = row_id(id) else {
+            return Ok(None);
+        };
+
+        self.with_conn(move |conn| {
+            conn.query_row(
+                &format!("SELECT {TICKET_COLUMNS} FROM ticket WHERE id = ?1"),
+                [id],
+                ticket_from_row,
+            )
+            .optional()
+            .map_err(sqlite_error)
+        })
+        .await
+    }
+
+    /// Only reads the page (and one more ticket), `WHERE (key, id) > cursor ... LIMIT n + 1`.
+    async fn list_tickets(&self, query: TicketListQuery) -> Result<TicketPage> {
+        // Of no ticket if not a rowid (e.g., `?cid=` from a client)
+        let no_row = |id: Option<u64>| id.is_some_and(|id| row_id(id).is_none());
+        if no_row(query.cid) || no_row(query.assignee.flatten()) {
+            return list::page(Vec::new(), &query);
+        }
+
+        let (key, cursor_key) = match query.sort {
+            TicketSort::Id => ("id".to_string(), "?10".to_string()),
+            TicketSort::Title => ("title".to_string(), "?9".to_string()),
+            TicketSort::Ctime => (
+                TICKET_CTIME_KEY.to_string(),
+                TICKET_CTIME_KEY.replace("ctime", "?9"),
+            ),
+        };
+        let (after, dir) = match query.order {
+            SortOrder::Asc => (">", "ASC"),
+            SortOrder::Desc => ("<", "DESC"),
+        };
+        let cursor = query.cursor.as_ref();
+        let cursor_title_or_ctime = match cursor {
+            Some(cursor) => match (&cursor.title, cursor.ctime) {
+                (Some(title), _) => Some(title.clone()),
+                (None, Some(ctime)) => Some(format_time(
+                    OffsetDateTime::from_unix_timestamp_nanos(ctime)
+                        .map_err(|ex| Error::StoreFail(ex.to_string()))?,
+                )?),
+                (None, None) => None,
+            },
+            None => None,
+        };
+        // Past the last rowid either way
+        let cursor_id = cursor.map(|cursor| cursor.id.min(i64::MAX as u64));
+        let assignee_filtered = query.assignee.is_some();
+        let assignee = query.assignee.flatten();
+
+        self.with_conn(move |conn| {
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT {TICKET_COLUMNS} FROM ticket
+                        WHERE (?11 IS NULL OR cid = ?11 OR assignee = ?11)
+                        AND (?1 IS NULL OR cid = ?1)
+                        AND (?2 IS NULL OR instr(lower(title), ?2) > 0)
+                        AND (NOT ?3 OR assignee IS ?4)
+                        AND (?5 IS NULL OR EXISTS (SELECT 1 FROM json_each(labels) WHERE value = ?5))
+                        AND (?6 IS NULL OR priority = ?6)
+                        AND (?7 OR deleted_at IS NULL)
+                        AND (?10 IS NULL OR ({key}, id) {after} ({cursor_key}, ?10))
+                        ORDER BY {key} {dir}, id {dir}
+                        LIMIT ?8"
+                ))
+                .map_err(sqlite_error)?;
+            let tickets = stmt
+                .query_map(
+                    params![
+                        query.cid,
+                        query.title_contains,
+                        assignee_filtered,
+                        assignee,
+                        query.label,
+                        query.priority,
+                        query.include_deleted,
+                        query.limit + 1,
+                        cursor_title_or_ctime,
+                        cursor_id,
+                        query.readable_by
+                    ],
+                    ticket_from_row,
+                )
+                .map_err(sqlite_error)?
+                .collect::<rusqlite::Result<Vec<_>>>()
+                .map_err(sqlite_error)?;
+
+            list::page(tickets, &query)
+        })
+        .await
+    }
+
+    /// Compared to the millisecond, as the ctime sort key.
+    async fn list_deleted_tickets(&self, deleted_before: OffsetDateTime) -> Result<Vec<Ticket>> {
+        let deleted_before = format_time(deleted_before)?;
+
+        self.with_conn(move |conn| {
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT {TICKET_COLUMNS} FROM ticket
+                        WHERE {} < {}
+                        ORDER BY id",
+                    TICKET_CTIME_KEY.replace("ctime", "deleted_at"),
+                    TICKET_CTIME_KEY.replace("ctime", "?1"),
+                ))
+                .map_err(sqlite_error)?;
+            let tickets = stmt
+                .query_map([deleted_before], ticket_from_row)
+                .map_err(sqlite_error)?
+                .collect::<rusqlite::Result<Vec<_>>>()
+                .map_err(sqlite_error)?;
+
+            Ok(tickets)
+        })
+        .await
+    }
+
+    async fn update_ticket(&self, ticket: &Ticket) -> Result<()> {
+        if row_id(ticket.id).is_none() {
+            return Err(Error::TicketNotFound { id: ticket.id });
+        }
+        let ticket = ticket.clone();
+
+        self.with_conn(move |conn| {
+            let count = conn
+                .execute(
+                    "UPDATE ticket SET cid = ?2, title = ?3, status = ?4,
+                        assignee = ?5, priority = ?6, labels = ?7, due = ?8,
+                        deleted_at = ?9, deleted_by = ?10, version = ?11
+                        WHERE id = ?1 AND version = ?11 - 1",
+                    params![
+                        ticket.id,
+                        ticket.cid,
+                        ticket.title,
+                        ticket.status,
+                        ticket.assignee,
+                        ticket.priority,
+                        to_json(&ticket.labels)?,
+                        ticket.due.map(format_time).transpose()?,
+                        ticket.deleted_at.map(format_time).transpose()?,
+                        ticket.deleted_by,
+                        ticket.version
+                    ],
+                )
+                .map_err(sqlite_error)?;
+
+            if count == 0 {
+                // Not there, or not the previous version
+                let actual: Option<u64> = conn
+                    .query_row(
+                        "SELECT version FROM ticket WHERE id = ?1",
+                        [ticket.id],
+                        |r| r.get(0),
+                    )
+                    .optional()
+                    .map_err(sqlite_error)?;
+
+                return Err(match actual {
+                    None => Error::TicketNotFound { id: ticket.id },
+                    Some(actual) => Error::TicketVersionConflict {
+                        id: ticket.id,
+                        expected: vec![ticket.version - 1],
+                        actual,
+                    },
+                });
+            }
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete_ticket(&self, id: u64) -> Result<Option<Ticket>> {
+        let Some(id) = row_id(id) else {
+            return Ok(None);
+        };
+
+        self.with_conn(move |conn| {
+            let tx = conn.transaction().map_err(sqlite_error)?;
+            let ticket = tx
+                .query_row(
+                    &format!(
+                        "DELETE FROM ticket WHERE id = ?1 AND deleted_at IS NOT NULL
+                        RETURNING {TICKET_COLUMNS}"
+                    ),
+                    [id],
+                    ticket_from_row,
+                )
+                .optional()
+                .map_err(sqlite_error)?;
+            if ticket.is_some() {
+                tx.execute("DELETE FROM comment WHERE ticket_id = ?1", [id])
+                    .map_err(sqlite_error)?;
+            }
+            tx.commit().map_err(sqlite_error)?;
+
+            Ok(ticket)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl CommentStore for SqliteStore {
+    async fn insert_comment(&self, mut comment: Comment) -> Result<Comment> {
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT INTO comment (ticket_id, author, body, ctime, mtime)
+                    VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    comment.ticket_id,
+                    comment.author,
+                    comment.body,
+                    format_time(comment.ctime)?,
+                    comment.mtime.map(format_time).transpose()?
+                ],
+            )
+            .map_err(sqlite_error)?;
+            comment.id = conn.last_insert_rowid() as u64;
+
+            Ok(comment)
+        })
+        .await
+    }
+
+    async fn get_comment(&self, id: u64) -> Result<Option<Comment>> {
+        let Some(id) = row_id(id) else {
+            return Ok(None);
+        };
+
+        self.with_conn(move |conn| {
+            conn.query_row(
+                &format!("SELECT {COMMENT_COLUMNS} FROM comment WHERE id = ?1"),
+                [id],
+                comment_from_row,
+            )
+            .optional()
+            .map_err(sqlite_error)
+        })
+        .await
+    }
+
+    async fn list_comments(&self, ticket_id: u64) -> Result<Vec<Comment>> {
+        let Some(ticket_id) = row_id(ticket_id) else {
+            return Ok(Vec::new());
+        };
+
+        self.with_conn(move |conn| {
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT {COMMENT_COLUMNS} FROM comment WHERE ticket_id = ?1 ORDER BY id"
+                ))
+                .map_err(sqlite_error)?;
+            let comments = stmt
+                .query_map([ticket_id], comment_from_row)
+                .map_err(sqlite_error)?
+                .collect::<rusqlite::Result<Vec<_>>>()
+                .map_err(sqlite_error)?;
+
+            Ok(comments)
+        })
+        .await
+    }
+
+    async fn update_comment(&self, comment: &Comment) -> Result<()> {
+        if row_id(comment.id).is_none() {
+            return Err(Error::CommentNotFound { id: comment.id });
+        }
+        let comment = comment.clone();
+
+        self.with_conn(move |conn| {
+            let count = conn
+                .execute(
+                    "UPDATE comment SET body = ?2, mtime = ?3 WHERE id = ?1",
+                    params![
+                        comment.id,
+                        comment.body,
+                        comment.mtime.map(format_time).transpose()?
+                    ],
+                )
+                .map_err(sqlite_error)?;
+
+            if count == 0 {
+                return Err(Error::CommentNotFound { id: comment.id });
+            }
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn delete_comment(&self, id: u64) -> Result<Option<Comment>> {
+        let Some(id) = row_id(id) else {
+            return Ok(None);
+        };
+
+        self.with_conn(move |conn| {
+            conn.query_row(
+                &format!("DELETE FROM comment WHERE id = ?1 RETURNING {COMMENT_COLUMNS}"),
+                [id],
+                comment_from_row,
+            )
+            .optional()
+            .map_err(sqlite_error)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl HistoryStore for SqliteStore {
+    async fn insert_history(&self, mut entry: HistoryEntry) -> Result<HistoryEntry> {
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT INTO ticket_history (ticket_id, actor, time, action, changes)
+                    VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    entry.ticket_id,
+                    entry.actor,
+                    format_time(entry.time)?,
+                    entry.action,
+                    to_json(&entry.changes)?
+                ],
+            )
+            .map_err(sqlite_error)?;
+            entry.id = conn.last_insert_rowid() as u64;
+
+            Ok(entry)
+        })
+        .await
+    }
+
+    async fn list_history(&self, ticket_id: u64) -> Result<Vec<HistoryEntry>> {
+        let Some(ticket_id) = row_id(ticket_id) else {
+            return Ok(Vec::new());
+        };
+
+        self.with_conn(move |conn| {
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT {HISTORY_COLUMNS} FROM ticket_history WHERE ticket_id = ?1 ORDER BY id"
+                ))
+                .map_err(sqlite_error)?;
+            let history = stmt
+                .query_map([ticket_id], history_from_row)
+                .map_err(sqlite_error)?
+                .collect::<rusqlite::Result<Vec<_>>>()
+                .map_err(sqlite_error)?;
+
+            Ok(history)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl UserStore for SqliteStore {
+    async fn insert_user(&self, mut user: User) -> Result<User> {
+        self.with_conn(move |conn| {
+            // The unique index is the uniqueness check, so registrations cannot race each other
+            let res = conn.execute(
+                "INSERT INTO user (username, pwd, token_salt, roles, ctime)
+                    VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    user.username,
+                    user.pwd,
+                    user.token_salt,
+                    to_json(&user.roles)?,
+                    format_time(user.ctime)?
+                ],
+            );
+            match res {
+                Err(rusqlite::Error::SqliteFailure(ex, _))
+                    if ex.code == ErrorCode::ConstraintViolation =>
+                {
+                    return Err(Error::UserUsernameAlreadyExists {
+                        username: user.username,
+                    });
+                }
+                res => res.map_err(sqlite_error)?,
+            };
+            user.id = conn.last_insert_rowid() as u64;
+
+            Ok(user)
+        })
+        .await
+    }
+
+    async fn get_user(&self, id: u64) -> Result<Option<User>> {
+        let Some(id) = row_id(id) else {
+            return Ok(None);
+        };
+
+        self.with_conn(move |conn| {
+            conn.query_row(
+                &format!("SELECT {USER_COLUMNS} FROM user WHERE id = ?1"),
+                [id],
+                user_from_row,
+            )
+            .optional()
+            .map_err(sqlite_error)
+        })
+        .await
+    }
+
+    async fn first_user_by_username(&self, username: &str) -> Result<Option<User>> {
+        let username = username.to_string();
+
+        self.with_conn(move |conn| {
+            // Exact match, as for the memory store (the index collation is only for uniqueness)
+            conn.query_row(
+                &format!("SELECT {USER_COLUMNS} FROM user WHERE username = ?1 COLLATE BINARY"),
+                [username],
+                user_from_row,
+            )
+            .optional()
+            .map_err(sqlite_error)
+        })
+        .await
+    }
+
+    async fn count_users(&self) -> Result<u64> {
+        self.with_conn(|conn| {
+            conn.query_row("SELECT COUNT(*) FROM user", [], |r| r.get(0))
+                .map_err(sqlite_error)
+        })
+        .await
+    }
+
+    async fn update_user_roles(&self, id: u64, roles: &[Role]) -> Result<()> {
+        let roles = to_json(roles)?;
+        let Some(rowid) = row_id(id) else {
+            return Err(Error::UserNotFound { id });
+        };
+
+        self.with_conn(move |conn| {
+            let count = conn
+                .execute(
+                    "UPDATE user SET roles = ?2 WHERE id = ?1",
+                    params![rowid, roles],
+                )
+                .map_err(sqlite_error)?;
+
+            if count == 0 {
+                return Err(Error::UserNotFound { id });
+            }
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn update_user_token_salt(&self, id: u64, token_salt: Uuid) -> Result<()> {
+        let Some(rowid) = row_id(id) else {
+            return Err(Error::UserNotFound { id });
+        };
+
+        self.with_conn(move |conn| {
+            let count = conn
+                .execute(
+                    "UPDATE user SET token_salt = ?2 WHERE id = ?1",
+                    params![rowid, token_salt],
+                )
+                .map_err(sqlite_error)?;
+
+            if count == 0 {
+                return Err(Error::UserNotFound { id });
+            }
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn revoke_session(&self, user_id: u64, origin: u64, until: u64, now: u64) -> Result<()> {
+        self.with_conn(move |conn| {
+            conn.execute("DELETE FROM revoked_session WHERE until <= ?1", [now])
+                .map_err(sqlite_error)?;
+            conn.execute(
+                "INSERT OR REPLACE INTO revoked_session (user_id, origin, until)
+                    VALUES (?1, ?2, ?3)",
+                params![user_id, origin, until],
+            )
+            .map_err(sqlite_error)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn is_session_revoked(&self, user_id: u64, origin: u64, now: u64) -> Result<bool> {
+        self.with_conn(move |conn| {
+            conn.query_row(
+                "SELECT EXISTS (SELECT 1 FROM revoked_session
+                    WHERE user_id = ?1 AND origin = ?2 AND until > ?3)",
+                params![user_id, origin, now],
+                |r| r.get(0),
+            )
+            .map_err(sqlite_error)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl ApiKeyStore for SqliteStore {
+    async fn insert_api_key(&self, api_key: ApiKey) -> Result<ApiKey> {
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT INTO api_key (id, user_id, name, key_hash, scopes, exp, ctime)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    api_key.id,
+                    api_key.user_id,
+                    api_key.name,
+                    api_key.key_hash,
+                    api_key.scopes.as_ref().map(to_json).transpose()?,
+                    api_key.exp.map(format_time).transpose()?,
+                    format_time(api_key.ctime)?
+                ],
+            )
+            .map_err(sqlite_error)?;
+
+            Ok(api_key)
+        })
+        .await
+    }
+
+    async fn get_api_key(&self, id: Uuid) -> Result<Option<ApiKey>> {
+        self.with_conn(move |conn| {
+            conn.query_row(
+                &format!("SELECT {API_KEY_COLUMNS} FROM api_key WHERE id = ?1"),
+                [id],
+                api_key_from_row,
+            )
+            .optional()
+            .map_err(sqlite_error)
+        })
+        .await
+    }
+
+    async fn list_api_keys(&self, user_id: u64) -> Result<Vec<ApiKey>> {
+        self.with_conn(move |conn| {
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT {API_KEY_COLUMNS} FROM api_key WHERE user_id = ?1 ORDER BY ctime"
+                ))
+                .map_err(sqlite_error)?;
+            let api_keys = stmt
+                .query_map([user_id], api_key_from_row)
+                .map_err(sqlite_error)?
+                .collect::<rusqlite::Result<Vec<_>>>()
+                .map_err(sqlite_error)?;
+
+            Ok(api_keys)
+        })
+        .await
+    }
+
+    async fn delete_api_key(&self, id: Uuid) -> Result<Option<ApiKey>> {
+        self.with_conn(move |conn| {
+            conn.query_row(
+                &format!("DELETE FROM api_key WHERE id = ?1 RETURNING {API_KEY_COLUMNS}"),
+                [id],
+                api_key_from_row,
+            )
+            .optional()
+            .map_err(sqlite_error)
+        })
+        .await
+    }
+}
+
+fn ticket_from_row(row: &Row) -> rusqlite::Result<Ticket> {
+    Ok(Ticket {
+        id: row.get(0)?,
+        cid: row.get(1)?,
+        title: row.get(2)?,
+        status: row.get(3)?,
+        assignee: row.get(4)?,
+        priority: row.get(5)?,
+        labels: json_from_row(row, 6)?,
+        due: parse_time_opt(row, 7)?,
+        ctime: parse_time(row, 8)?,
+        deleted_at: parse_time_opt(row, 9)?,
+        deleted_by: row.get(10)?,
+        version: row.get(11)?,
+    })
+}
+
+fn comment_from_row(row: &Row) -> rusqlite::Result<Comment> {
+    Ok(Comment {
+        id: row.get(0)?,
+        ticket_id: row.get(1)?,
+        author: row.get(2)?,
+        body: row.get(3)?,
+        ctime: parse_time(row, 4)?,
+        mtime: parse_time_opt(row, 5)?,
+    })
+}
+
+fn history_from_row(row: &Row) -> rusqlite::Result<HistoryEntry> {
+    Ok(HistoryEntry {
+        id: row.get(0)?,
+        ticket_id: row.get(1)?,
+        actor: row.get(2)?,
+        time: parse_time(row, 3)?,
+        action: row.get(4)?,
+        changes: json_from_row(row, 5)?,
+    })
+}
+
+fn user_from_row(row: &Row) -> rusqlite::Result<User> {
+    Ok(User {
+        id: row.get(0)?,
+        username: row.get(1)?,
+        pwd: row.get(2)?,
+        token_salt: row.get(3)?,
+        roles: json_from_row(row, 4)?,
+        ctime: parse_time(row, 5)?,
+    })
+}
+
+fn api_key_from_row(row: &Row) -> rusqlite::Result<ApiKey> {
+    Ok(ApiKey {
+        id: row.get(0)?,
+        user_id: row.get(1)?,
+        name: row.get(2)?,
+        key_hash: row.get(3)?,
+        scopes: json_from_row_opt(row, 4)?,
+        exp: parse_time_opt(row, 5)?,
+        ctime: parse_time(row, 6)?,
+    })
+}
+
+fn format_time(time: OffsetDateTime) -> Result<String> {
+    time.format(&Rfc3339)
+        .map_err(|ex| Error::StoreFail(ex.to_string()))
+}
+
+fn parse_time(row: &Row, idx: usize) -> rusqlite::Result<OffsetDateTime> {
+    let time: String = row.get(idx)?;
+
+    OffsetDateTime::parse(&time, &Rfc3339).map_err(|ex| conversion_error(idx, ex))
+}
+
+fn parse_time_opt(row: &Row, idx: usize) -> rusqlite::Result<Option<OffsetDateTime>> {
+    let time: Option<String> = row.get(idx)?;
+
+    time.map(|time| OffsetDateTime::parse(&time, &Rfc3339).map_err(|ex| conversion_error(idx, ex)))
+        .transpose()
+}
+
+/// For the collection columns (e.g., roles, labels).
+fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(|ex| Error::StoreFail(ex.to_string()))
+}
+
+fn json_from_row<T: DeserializeOwned>(row: &Row, idx: usize) -> rusqlite::Result<T> {
+    let json: String = row.get(idx)?;
+
+    serde_json::from_str(&json).map_err(|ex| conversion_error(idx, ex))
+}
+
+fn json_from_row_opt<T: DeserializeOwned>(row: &Row, idx: usize) -> rusqlite::Result<Option<T>> {
+    let json: Option<String> = row.get(idx)?;
+
+    json.map(|json| serde_json::from_str(&json).map_err(|ex| conversion_error(idx, ex)))
+        .transpose()
+}
+
+fn conversion_error(
+    idx: usize,
+    ex: impl std::error::Error + Send + Sync + 'static,
+) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(ex))
+}
+
+/// Stored as their snake_case name, as in the JSON.
+macro_rules! impl_sql_for_unit_enum {
+    ($($enum:ty),*) => {$(
+        impl ToSql for $enum {
+            fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+                Ok(self.as_ref().into())
+            }
+        }
+
+        impl FromSql for $enum {
+            fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+                let name = value.as_str()?;
+
+                serde_json::from_value(serde_json::Value::from(name))
+                    .map_err(|ex| FromSqlError::Other(Box::new(ex)))
+            }
+        }
+    )*};
+}
+
+impl_sql_for_unit_enum!(TicketStatus, TicketPriority, HistoryAction);
+
+/// The ids are rowids, `None` for the larger ones (which are of no row).
+fn row_id(id: u64) -> Option<i64> {
+    i64::try_from(id).ok()
+}
+
+fn sqlite_error(ex: rusqlite::Error) -> Error {
+    Error::StoreFail(ex.to_string())
+}
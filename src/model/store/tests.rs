@@ -0,0 +1,331 @@
+//! The same cases against every store, so the backends behave alike.
+
+use std::sync::Arc;
+
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::{MemoryStore, SqliteStore, Store};
+use crate::Error;
+use crate::ctx::Role;
+use crate::model::list::{SortOrder, TicketListOptions, TicketListQuery, TicketSort};
+use crate::model::{Comment, Ticket, TicketFilter, TicketPriority, TicketStatus, User};
+
+type TestResult = core::result::Result<(), Box<dyn std::error::Error>>;
+
+// region: --- Helpers
+fn stores() -> Vec<(&'static str, Arc<dyn Store>)> {
+    vec![
+        ("memory", MemoryStore::new()),
+        ("sqlite", Arc::new(SqliteStore::open(":memory:").unwrap())),
+    ]
+}
+
+fn new_ticket(cid: u64, title: &str) -> Ticket {
+    Ticket {
+        id: 0,
+        cid,
+        title: title.to_string(),
+        status: TicketStatus::Open,
+        assignee: None,
+        priority: TicketPriority::Medium,
+        labels: Default::default(),
+        due: None,
+        ctime: OffsetDateTime::now_utc(),
+        deleted_at: None,
+        deleted_by: None,
+        version: 1,
+    }
+}
+
+fn new_user(username: &str) -> User {
+    User {
+        id: 0,
+        username: username.to_string(),
+        pwd: "not-a-hash".to_string(),
+        token_salt: Uuid::new_v4(),
+        roles: vec![Role::Reporter],
+        ctime: OffsetDateTime::now_utc(),
+    }
+}
+// endregion: --- Helpers
+
+#[tokio::test]
+async fn test_ticket_insert_get() -> TestResult {
+    for (name, store) in stores() {
+        let mut ticket = new_ticket(1, "First");
+        ticket.assignee = Some(2);
+        ticket.labels.insert("bug".to_string());
+        let ticket = store.insert_ticket(ticket).await?;
+
+        let got = store.get_ticket(ticket.id).await?.ok_or(name)?;
+        assert_eq!(got.id, ticket.id, "{name}");
+        assert_eq!(got.title, "First", "{name}");
+        assert_eq!(got.assignee, Some(2), "{name}");
+        assert!(got.labels.contains("bug"), "{name}");
+        assert_eq!(got.version, 1, "{name}");
+
+        assert!(store.get_ticket(ticket.id + 1).await?.is_none(), "{name}");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ticket_id_out_of_rowid_range() -> TestResult {
+    for (name, store) in stores() {
+        let ticket = store.insert_ticket(new_ticket(1, "First")).await?;
+
+        assert!(store.get_ticket(u64::MAX).await?.is_none(), "{name}");
+        assert!(store.delete_ticket(u64::MAX).await?.is_none(), "{name}");
+        assert!(store.list_comments(u64::MAX).await?.is_empty(), "{name}");
+        assert!(store.get_user(u64::MAX).await?.is_none(), "{name}");
+
+        let mut missing = ticket.clone();
+        missing.id = u64::MAX;
+        missing.version = 2;
+        let res = store.update_ticket(&missing).await;
+        assert!(
+            matches!(res, Err(Error::TicketNotFound { .. })),
+            "{name}: {res:?}"
+        );
+
+        let mut query = TicketListQuery::new(None, 1, TicketFilter::default(), Default::default())?;
+        query.cid = Some(u64::MAX);
+        let page = store.list_tickets(query).await?;
+        assert!(page.items.is_empty(), "{name}");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ticket_update_version_conflict() -> TestResult {
+    for (name, store) in stores() {
+        let ticket = store.insert_ticket(new_ticket(1, "First")).await?;
+
+        let mut update = ticket.clone();
+        update.title = "Second".to_string();
+        update.version = 2;
+        store.update_ticket(&update).await?;
+
+        // Made from the same version 1, so it would overwrite the first update
+        let mut stale = ticket.clone();
+        stale.title = "Third".to_string();
+        stale.version = 2;
+        let res = store.update_ticket(&stale).await;
+        assert!(
+            matches!(
+                res,
+                Err(Error::TicketVersionConflict {
+                    ref expected,
+                    actual: 2,
+                    ..
+                }) if expected == &[1]
+            ),
+            "{name}: {res:?}"
+        );
+
+        let got = store.get_ticket(ticket.id).await?.ok_or(name)?;
+        assert_eq!(got.title, "Second", "{name}");
+
+        let mut missing = new_ticket(1, "Missing");
+        missing.id = ticket.id + 1;
+        missing.version = 2;
+        let res = store.update_ticket(&missing).await;
+        assert!(
+            matches!(res, Err(Error::TicketNotFound { .. })),
+            "{name}: {res:?}"
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ticket_delete_only_soft_deleted() -> TestResult {
+    for (name, store) in stores() {
+        let ticket = store.insert_ticket(new_ticket(1, "First")).await?;
+        let comment = store
+            .insert_comment(Comment {
+                id: 0,
+                ticket_id: ticket.id,
+                author: 1,
+                body: "A comment".to_string(),
+                ctime: OffsetDateTime::now_utc(),
+                mtime: None,
+            })
+            .await?;
+
+        // Not soft deleted, so kept
+        assert!(store.delete_ticket(ticket.id).await?.is_none(), "{name}");
+        assert!(store.get_ticket(ticket.id).await?.is_some(), "{name}");
+
+        let mut deleted = ticket.clone();
+        deleted.deleted_at = Some(OffsetDateTime::now_utc());
+        deleted.deleted_by = Some(1);
+        deleted.version = 2;
+        store.update_ticket(&deleted).await?;
+        let deleted_at = deleted.deleted_at.ok_or(name)?;
+        let before = deleted_at - time::Duration::seconds(1);
+        let after = deleted_at + time::Duration::seconds(1);
+        assert!(
+            store.list_deleted_tickets(before).await?.is_empty(),
+            "{name}"
+        );
+        assert_eq!(store.list_deleted_tickets(after).await?.len(), 1, "{name}");
+
+        let purged = store.delete_ticket(ticket.id).await?.ok_or(name)?;
+        assert_eq!(purged.id, ticket.id, "{name}");
+        assert!(store.get_ticket(ticket.id).await?.is_none(), "{name}");
+        assert!(store.get_comment(comment.id).await?.is_none(), "{name}");
+        assert!(store.delete_ticket(ticket.id).await?.is_none(), "{name}");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_user_username_unique() -> TestResult {
+    for (name, store) in stores() {
+        let user = store.insert_user(new_user("demo1")).await?;
+        assert_eq!(store.count_users().await?, 1, "{name}");
+
+        // Case insensitive
+        let res = store.insert_user(new_user("Demo1")).await;
+        assert!(
+            matches!(res, Err(Error::UserUsernameAlreadyExists { .. })),
+            "{name}: {res:?}"
+        );
+        assert_eq!(store.count_users().await?, 1, "{name}");
+
+        // The lookup itself is exact
+        let got = store.first_user_by_username("demo1").await?.ok_or(name)?;
+        assert_eq!(got.id, user.id, "{name}");
+        assert!(
+            store.first_user_by_username("DEMO1").await?.is_none(),
+            "{name}"
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ticket_list_pages() -> TestResult {
+    // Duplicate titles and ctimes, so the pages split ties (broken by the id)
+    let titles = ["b", "a", "c", "a", "b", "a", "c"];
+    let ctime = |i: usize| OffsetDateTime::from_unix_timestamp(1_700_000_000 + (i % 3) as i64);
+
+    for (name, store) in stores() {
+        let mut tickets = Vec::new();
+        for (i, title) in titles.iter().enumerate() {
+            let mut ticket = new_ticket(1, title);
+            ticket.ctime = ctime(i)?;
+            tickets.push(store.insert_ticket(ticket).await?);
+        }
+        let mut deleted = store.insert_ticket(new_ticket(1, "a")).await?;
+        deleted.deleted_at = Some(OffsetDateTime::now_utc());
+        deleted.version = 2;
+        store.update_ticket(&deleted).await?;
+
+        for sort in [TicketSort::Id, TicketSort::Title, TicketSort::Ctime] {
+            for order in [SortOrder::Asc, SortOrder::Desc] {
+                let mut expected = tickets.clone();
+                expected.sort_by(|a, b| {
+                    let key_ord = match sort {
+                        TicketSort::Id => std::cmp::Ordering::Equal,
+                        TicketSort::Title => a.title.cmp(&b.title),
+                        TicketSort::Ctime => a.ctime.cmp(&b.ctime),
+                    };
+                    key_ord.then(a.id.cmp(&b.id))
+                });
+                if order == SortOrder::Desc {
+                    expected.reverse();
+                }
+                let expected: Vec<u64> = expected.iter().map(|t| t.id).collect();
+
+                let mut ids = Vec::new();
+                let mut cursor = None;
+                loop {
+                    let options = TicketListOptions {
+                        limit: Some(2),
+                        cursor,
+                        sort,
+                        order,
+                    };
+                    let query = TicketListQuery::new(None, 1, TicketFilter::default(), options)?;
+                    let page = store.list_tickets(query).await?;
+                    assert!(page.items.len() <= 2, "{name} {sort:?} {order:?}");
+                    ids.extend(page.items.iter().map(|t| t.id));
+
+                    cursor = page.next_cursor;
+                    if cursor.is_none() {
+                        break;
+                    }
+                }
+
+                assert_eq!(ids, expected, "{name} {sort:?} {order:?}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ticket_list_cursor_of_other_sort() -> TestResult {
+    for (name, store) in stores() {
+        for title in ["a", "b", "c"] {
+            store.insert_ticket(new_ticket(1, title)).await?;
+        }
+
+        let options = TicketListOptions {
+            limit: Some(1),
+            sort: TicketSort::Title,
+            ..Default::default()
+        };
+        let query = TicketListQuery::new(None, 1, TicketFilter::default(), options)?;
+        let page = store.list_tickets(query).await?;
+        assert!(page.next_cursor.is_some(), "{name}");
+
+        let options = TicketListOptions {
+            limit: Some(1),
+            cursor: page.next_cursor,
+            sort: TicketSort::Title,
+            order: SortOrder::Desc,
+        };
+        let res = TicketListQuery::new(None, 1, TicketFilter::default(), options);
+        assert!(
+            matches!(res, Err(Error::TicketListCursorInvalid)),
+            "{name}: {res:?}"
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ticket_list_readable_by() -> TestResult {
+    for (name, store) in stores() {
+        let created = store.insert_ticket(new_ticket(2, "Created")).await?;
+        let mut assigned = new_ticket(1, "Assigned");
+        assigned.assignee = Some(2);
+        let assigned = store.insert_ticket(assigned).await?;
+        store.insert_ticket(new_ticket(1, "Other")).await?;
+
+        let mut query =
+            TicketListQuery::new(Some(2), 2, TicketFilter::default(), Default::default())?;
+        let page = store.list_tickets(query.clone()).await?;
+        let ids: Vec<u64> = page.items.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![created.id, assigned.id], "{name}");
+
+        // And the other filters on top
+        query.cid = Some(1);
+        let page = store.list_tickets(query).await?;
+        let ids: Vec<u64> = page.items.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![assigned.id], "{name}");
+    }
+
+    Ok(())
+}
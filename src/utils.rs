@@ -0,0 +1,10 @@
+use time::OffsetDateTime;
+
+pub fn now_utc() -> OffsetDateTime {
+    OffsetDateTime::now_utc()
+}
+
+/// Current time in seconds since UNIX epoch.
+pub fn now_utc_sec() -> u64 {
+    now_utc().unix_timestamp() as u64
+}
@@ -0,0 +1,287 @@
+//! Request extractors that fail with our `Error`, so that rejections go through
+//! `main_response_mapper` like any other client error.
+//!
+//! Errors on the body as a whole (e.g., syntax ones) are reported on the `$` field.
+
+use std::borrow::Cow;
+
+use axum::{
+    body::Bytes,
+    extract::{
+        FromRequest, FromRequestParts, Path, RawPathParams, Request, path::ErrorKind,
+        rejection::PathRejection,
+    },
+    http::{HeaderMap, header, request::Parts},
+};
+use lazy_regex::regex_captures;
+use serde::de::DeserializeOwned;
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
+
+use crate::error::FieldError;
+use crate::{Error, Result};
+
+const BODY_FIELD: &str = "$";
+
+/// JSON body, deserialized then validated (its `Validate` rules).
+///
+/// Malformed bodies fail with `BodyFailJson`, invalid ones with `BodyFailValidation`,
+/// both listing the offending fields.
+pub struct ValidJson<T>(pub T);
+
+impl<S, T> FromRequest<S> for ValidJson<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Validate,
+{
+    type Rejection = Error;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self> {
+        if !has_json_content_type(req.headers()) {
+            return Err(body_fail_json(
+                BODY_FIELD,
+                "expected an 'application/json' content type".to_string(),
+            ));
+        }
+
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(|rejection| body_fail_json(BODY_FIELD, rejection.body_text()))?;
+
+        let value: T = deserialize_json(&bytes)?;
+        value
+            .validate()
+            .map_err(|errors| Error::BodyFailValidation {
+                errors: field_errors(&errors),
+            })?;
+
+        Ok(Self(value))
+    }
+}
+
+/// Query string, deserialized then validated (its `Validate` rules).
+///
+/// Both fail with `QueryFailParams`, listing the offending parameters.
+pub struct ValidQuery<T>(pub T);
+
+impl<S, T> FromRequestParts<S> for ValidQuery<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Validate,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self> {
+        let query = parts.uri.query().unwrap_or_default();
+
+        let deserializer =
+            serde_urlencoded::Deserializer::new(form_urlencoded::parse(query.as_bytes()));
+        let value: T = serde_path_to_error::deserialize(deserializer).map_err(|err| {
+            let path = err.path().to_string();
+            let message = err.into_inner().to_string();
+            match path.as_str() {
+                "." => query_fail_params(BODY_FIELD, message),
+                _ => query_fail_params(path, message),
+            }
+        })?;
+        value.validate().map_err(|errors| Error::QueryFailParams {
+            errors: field_errors(&errors),
+        })?;
+
+        Ok(Self(value))
+    }
+}
+
+/// Path parameters, parsed (e.g., `Path<u64>` ids), failing with `PathFailParams`.
+pub struct ValidPath<T>(pub T);
+
+impl<S, T> FromRequestParts<S> for ValidPath<T>
+where
+    S: Send + Sync,
+    T: DeserializeOwned + Send,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self> {
+        match Path::<T>::from_request_parts(parts, state).await {
+            Ok(Path(value)) => Ok(Self(value)),
+            Err(rejection) => {
+                // To name the unnamed ones (e.g., of `Path<u64>` or tuples)
+                let names: Vec<String> = RawPathParams::from_request_parts(parts, state)
+                    .await
+                    .map(|params| params.iter().map(|(name, _)| name.to_string()).collect())
+                    .unwrap_or_default();
+
+                Err(path_fail_params(rejection, &names))
+            }
+        }
+    }
+}
+
+fn path_fail_params(rejection: PathRejection, names: &[String]) -> Error {
+    let name_at = |index: usize| {
+        names
+            .get(index)
+            .cloned()
+            .unwrap_or_else(|| BODY_FIELD.to_string())
+    };
+
+    let (field, message) = match &rejection {
+        PathRejection::FailedToDeserializePathParams(err) => match err.kind() {
+            ErrorKind::ParseErrorAtKey {
+                key,
+                value,
+                expected_type,
+            } => (
+                key.clone(),
+                format!("'{value}' is not a valid {expected_type}"),
+            ),
+            ErrorKind::ParseErrorAtIndex {
+                index,
+                value,
+                expected_type,
+            } => (
+                name_at(*index),
+                format!("'{value}' is not a valid {expected_type}"),
+            ),
+            ErrorKind::ParseError {
+                value,
+                expected_type,
+            } => (
+                name_at(0),
+                format!("'{value}' is not a valid {expected_type}"),
+            ),
+            kind if names.len() == 1 => (name_at(0), kind.to_string()),
+            _ => (BODY_FIELD.to_string(), err.body_text()),
+        },
+        _ => (BODY_FIELD.to_string(), rejection.body_text()),
+    };
+
+    Error::PathFailParams {
+        errors: vec![FieldError {
+            field: field.into(),
+            message,
+        }],
+    }
+}
+
+fn has_json_content_type(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return false;
+    };
+
+    let mime = content_type.split(';').next().unwrap_or_default().trim();
+    mime == "application/json" || (mime.starts_with("application/") && mime.ends_with("+json"))
+}
+
+// region: --- Deserialization
+fn deserialize_json<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|err| {
+        let path = err.path().to_string();
+        let err = err.into_inner();
+        let message = err.to_string();
+        if err.is_syntax() || err.is_eof() {
+            return body_fail_json(BODY_FIELD, message);
+        }
+
+        // Missing fields are reported on their parent, report them on themselves
+        if let Some((_, field)) = regex_captures!(r#"^missing field `([^`]+)`"#, &message) {
+            let field = match path.as_str() {
+                "." => field.to_string(),
+                parent => format!("{parent}.{field}"),
+            };
+            return body_fail_json(field, "is required".to_string());
+        }
+
+        match path.as_str() {
+            "." => body_fail_json(BODY_FIELD, message),
+            _ => body_fail_json(path, message),
+        }
+    })?;
+
+    // No trailing content after the JSON value
+    deserializer
+        .end()
+        .map_err(|err| body_fail_json(BODY_FIELD, err.to_string()))?;
+
+    Ok(value)
+}
+
+fn query_fail_params(field: impl Into<Cow<'static, str>>, message: String) -> Error {
+    Error::QueryFailParams {
+        errors: vec![FieldError {
+            field: field.into(),
+            message,
+        }],
+    }
+}
+
+fn body_fail_json(field: impl Into<Cow<'static, str>>, message: String) -> Error {
+    Error::BodyFailJson {
+        errors: vec![FieldError {
+            field: field.into(),
+            message,
+        }],
+    }
+}
+// endregion: --- Deserialization
+
+// region: --- Validation
+/// Flattens the (possibly nested) validation errors, sorted by field
+/// (e.g., `items[2].name` for nested ones).
+fn field_errors(errors: &ValidationErrors) -> Vec<FieldError> {
+    let mut field_errors = Vec::new();
+    push_field_errors(&mut field_errors, None, errors);
+    field_errors.sort_by(|a, b| a.field.cmp(&b.field));
+
+    field_errors
+}
+
+fn push_field_errors(acc: &mut Vec<FieldError>, parent: Option<&str>, errors: &ValidationErrors) {
+    for (field, kind) in errors.errors() {
+        let path = match parent {
+            None => field.to_string(),
+            Some(parent) => format!("{parent}.{field}"),
+        };
+
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                acc.extend(errors.iter().map(|error| FieldError {
+                    field: path.clone().into(),
+                    message: validation_message(error),
+                }));
+            }
+            ValidationErrorsKind::Struct(errors) => push_field_errors(acc, Some(&path), errors),
+            ValidationErrorsKind::List(items) => {
+                for (index, errors) in items {
+                    push_field_errors(acc, Some(&format!("{path}[{index}]")), errors);
+                }
+            }
+        }
+    }
+}
+
+/// The rule message if any, a default one for the built-in rules otherwise.
+fn validation_message(error: &ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+
+    let param = |name: &str| error.params.get(name).map(|v| v.to_string());
+    match (error.code.as_ref(), param("min"), param("max")) {
+        ("length", Some(min), Some(max)) => format!("must be between {min} and {max} characters"),
+        ("length", Some(min), None) => format!("must be at least {min} characters"),
+        ("length", None, Some(max)) => format!("must be at most {max} characters"),
+        ("range", Some(min), Some(max)) => format!("must be between {min} and {max}"),
+        ("range", Some(min), None) => format!("must be at least {min}"),
+        ("range", None, Some(max)) => format!("must be at most {max}"),
+        ("required", ..) => "is required".to_string(),
+        ("regex", ..) => "has an invalid format".to_string(),
+        (code, ..) => code.to_string(),
+    }
+}
+// endregion: --- Validation
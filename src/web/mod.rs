@@ -1,5 +1,31 @@
+use tower_cookies::{Cookie, Cookies};
+
+use crate::crypt::token::Token;
+
+pub mod extract;
 pub mod mw_auth;
+pub mod mw_req_stamp;
+pub mod problem;
+pub mod routes_admin;
+pub mod routes_api_key;
+pub mod routes_comment;
 pub mod routes_login;
 pub mod routes_ticket;
+pub mod routes_user;
 
 pub const AUTH_TOKEN: &str = "auth-token";
+
+fn set_token_cookie(cookies: &Cookies, token: &Token) {
+    let mut cookie = Cookie::new(AUTH_TOKEN, token.to_string());
+    cookie.set_http_only(true);
+    cookie.set_path("/");
+
+    cookies.add(cookie);
+}
+
+fn remove_token_cookie(cookies: &Cookies) {
+    let mut cookie = Cookie::from(AUTH_TOKEN);
+    cookie.set_path("/");
+
+    cookies.remove(cookie);
+}
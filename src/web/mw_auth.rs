@@ -1,46 +1,69 @@
-use crate::ctx::Ctx;
-use crate::web::AUTH_TOKEN;
+use crate::crypt::api_key::{ApiKeyToken, validate_api_key};
+use crate::crypt::token::{Token, renew_web_token, should_renew_web_token, validate_web_token};
+use crate::ctx::{Ctx, Permission};
+use crate::model::ModelController;
+use crate::utils::now_utc;
+use crate::web::{AUTH_TOKEN, remove_token_cookie, set_token_cookie};
 use crate::{Error, Result};
 use axum::body::Body;
-use axum::extract::FromRequestParts;
-use axum::http::Request;
+use axum::extract::{FromRequestParts, State};
+use axum::http::header::AUTHORIZATION;
 use axum::http::request::Parts;
+use axum::http::{HeaderMap, Request};
 use axum::middleware::Next;
 use axum::response::Response;
-use lazy_regex::regex_captures;
-use tower_cookies::{Cookie, Cookies};
+use tower_cookies::Cookies;
+use tracing::{Span, debug};
 
 pub async fn mw_require_auth(ctx: Result<Ctx>, req: Request<Body>, next: Next) -> Result<Response> {
-    println!("->> {:<12} - mw_require_auth - {ctx:?}", "MIDDLEWARE");
+    debug!("{:<12} - mw_require_auth - {ctx:?}", "MIDDLEWARE");
 
     ctx?; // Just to check if ctx is Ok, otherwise return the error
 
     Ok(next.run(req).await)
 }
 
+/// Route layer requiring a permission, e.g.:
+/// `.route_layer(middleware::from_fn_with_state(Permission::UserManage, mw_require_permission))`
+pub async fn mw_require_permission(
+    State(permission): State<Permission>,
+    ctx: Result<Ctx>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response> {
+    debug!(
+        "{:<12} - mw_require_permission - {permission:?}",
+        "MIDDLEWARE"
+    );
+
+    if !ctx?.has_permission(permission) {
+        return Err(Error::AuthFailNoPermission { permission });
+    }
+
+    Ok(next.run(req).await)
+}
+
 pub async fn mw_ctx_resolver(
+    State(mc): State<ModelController>,
     cookies: Cookies,
     mut req: Request<Body>,
     next: Next,
 ) -> Result<Response> {
-    println!("->> {:<12} - mw_ctx_resolver", "MIDDLEWARE");
-
-    let auth_token = cookies.get(AUTH_TOKEN).map(|c| c.value().to_string());
+    debug!("{:<12} - mw_ctx_resolver", "MIDDLEWARE");
 
-    let result_ctx = match auth_token
-        .ok_or(Error::AuthFailNoAuthTokenCookie)
-        .and_then(parse_token)
-    {
-        Ok((user_id, _exp, _sign)) => {
-            // TODO: Token components validation
-            Ok(Ctx::new(user_id))
-        }
-        Err(e) => Err(e),
+    let credential = auth_credential(req.headers(), &cookies);
+    let result_ctx = match credential.clone() {
+        Ok(credential) => ctx_resolve(&mc, &cookies, credential).await,
+        Err(ex) => Err(ex),
     };
 
-    // Remove the cookie if something went wrong other than NoAuthTokenCookie
-    if result_ctx.is_err() && !matches!(result_ctx, Err(Error::AuthFailNoAuthTokenCookie)) {
-        cookies.remove(Cookie::from(AUTH_TOKEN));
+    // Remove the cookie if it was the credential, and something went wrong
+    if result_ctx.is_err() && matches!(credential, Ok(AuthCredential::CookieToken(_))) {
+        remove_token_cookie(&cookies);
+    }
+
+    if let Ok(ctx) = &result_ctx {
+        Span::current().record("user_id", ctx.user_id());
     }
 
     // Store ctx_result in the request extension
@@ -49,12 +72,110 @@ pub async fn mw_ctx_resolver(
     Ok(next.run(req).await)
 }
 
+#[derive(Clone)]
+pub(crate) enum AuthCredential {
+    CookieToken(String),
+    BearerToken(String),
+    BearerApiKey(String),
+}
+
+/// The `Authorization` header takes precedence over the cookie.
+pub(crate) fn auth_credential(headers: &HeaderMap, cookies: &Cookies) -> Result<AuthCredential> {
+    if let Some(authorization) = headers.get(AUTHORIZATION) {
+        let bearer = authorization
+            .to_str()
+            .ok()
+            .and_then(|v| v.strip_prefix("Bearer "))
+            .map(|v| v.trim().to_string())
+            .ok_or(Error::AuthFailTokenWrongFormat)?;
+
+        return Ok(if bearer.starts_with("key-") {
+            AuthCredential::BearerApiKey(bearer)
+        } else {
+            AuthCredential::BearerToken(bearer)
+        });
+    }
+
+    cookies
+        .get(AUTH_TOKEN)
+        .map(|c| AuthCredential::CookieToken(c.value().to_string()))
+        .ok_or(Error::AuthFailNoAuthTokenCookie)
+}
+
+async fn ctx_resolve(
+    mc: &ModelController,
+    cookies: &Cookies,
+    credential: AuthCredential,
+) -> Result<Ctx> {
+    match credential {
+        AuthCredential::CookieToken(token) => {
+            ctx_resolve_web_token(mc, token.parse()?, Some(cookies)).await
+        }
+        AuthCredential::BearerToken(token) => ctx_resolve_web_token(mc, token.parse()?, None).await,
+        AuthCredential::BearerApiKey(key) => ctx_resolve_api_key(mc, key.parse()?).await,
+    }
+}
+
+/// The cookie, when given, is renewed when the token is close to expiring.
+async fn ctx_resolve_web_token(
+    mc: &ModelController,
+    token: Token,
+    cookies: Option<&Cookies>,
+) -> Result<Ctx> {
+    // -- Validate the token against the user token salt
+    let user = mc
+        .get_user(token.user_id)
+        .await?
+        .ok_or(Error::AuthFailUnknownUser {
+            user_id: token.user_id,
+        })?;
+    let salt = user.token_salt.to_string();
+    validate_web_token(&token, &salt)?;
+
+    if mc.is_session_revoked(token.user_id, token.origin).await? {
+        return Err(Error::AuthFailTokenRevoked);
+    }
+
+    // -- Sliding expiration, renew the cookie when close to expiring
+    if let Some(cookies) = cookies
+        && should_renew_web_token(&token)
+    {
+        let token = renew_web_token(&token, &salt)?;
+        set_token_cookie(cookies, &token);
+    }
+
+    Ok(Ctx::new(user.id, user.roles, None))
+}
+
+async fn ctx_resolve_api_key(mc: &ModelController, key: ApiKeyToken) -> Result<Ctx> {
+    let api_key = mc
+        .get_api_key(key.id)
+        .await?
+        .ok_or(Error::AuthFailApiKeyInvalid)?;
+
+    validate_api_key(&key, &api_key.key_hash)?;
+
+    if api_key.exp.is_some_and(|exp| exp <= now_utc()) {
+        return Err(Error::AuthFailApiKeyExpired);
+    }
+
+    // Roles from the user, so role changes apply to its keys
+    let user = mc
+        .get_user(api_key.user_id)
+        .await?
+        .ok_or(Error::AuthFailUnknownUser {
+            user_id: api_key.user_id,
+        })?;
+
+    Ok(Ctx::new(user.id, user.roles, api_key.scopes))
+}
+
 // region: --- Ctx Extractor
 impl<S: Send + Sync> FromRequestParts<S> for Ctx {
     type Rejection = Error;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self> {
-        println!("->> {:<12} - Ctx", "EXTRACTOR");
+        debug!("{:<12} - Ctx", "EXTRACTOR");
 
         parts
             .extensions
@@ -64,16 +185,3 @@ impl<S: Send + Sync> FromRequestParts<S> for Ctx {
     }
 }
 // endregion: --- Ctx Extractor
-
-/// Parse a token of format `user-[user-id].[expiration].[signature]`
-/// Returns (user_id, expiration, signature)
-fn parse_token(token: String) -> Result<(u64, String, String)> {
-    let (_whole, user_id, exp, sign) = regex_captures!(r#"^user-(\d+)\.(.+)\.(.+)"#, &token)
-        .ok_or(Error::AuthFailTokenWrongFormat)?;
-
-    let user_id: u64 = user_id
-        .parse()
-        .map_err(|_| Error::AuthFailTokenWrongFormat)?;
-
-    Ok((user_id, exp.to_string(), sign.to_string()))
-}
@@ -0,0 +1,62 @@
+use crate::utils::now_utc;
+use crate::{Error, Result};
+use axum::body::Body;
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::Request;
+use axum::http::request::Parts;
+use axum::middleware::Next;
+use axum::response::Response;
+use std::net::{IpAddr, SocketAddr};
+use time::OffsetDateTime;
+use tracing::{Instrument, debug, field, info_span};
+use uuid::Uuid;
+
+/// Identity, arrival time and origin of the request, for the logs and the client errors.
+#[derive(Clone, Debug)]
+pub struct ReqStamp {
+    pub uuid: Uuid,
+    pub time_in: OffsetDateTime,
+    pub client_ip: Option<IpAddr>, // None when served without `ConnectInfo`
+}
+
+/// Outermost layer, stamps the request and runs it within its `request` span
+/// (`user_id` is recorded once the ctx is resolved).
+pub async fn mw_req_stamp(mut req: Request<Body>, next: Next) -> Response {
+    let stamp = ReqStamp {
+        uuid: Uuid::new_v4(),
+        time_in: now_utc(),
+        client_ip: req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip()),
+    };
+
+    let span = info_span!(
+        "request",
+        req_uuid = %stamp.uuid,
+        method = %req.method(),
+        path = req.uri().path(),
+        user_id = field::Empty,
+    );
+    span.in_scope(|| debug!("{:<12} - mw_req_stamp", "MIDDLEWARE"));
+
+    req.extensions_mut().insert(stamp);
+
+    next.run(req).instrument(span).await
+}
+
+// region: --- ReqStamp Extractor
+impl<S: Send + Sync> FromRequestParts<S> for ReqStamp {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self> {
+        debug!("{:<12} - ReqStamp", "EXTRACTOR");
+
+        parts
+            .extensions
+            .get::<ReqStamp>()
+            .cloned()
+            .ok_or(Error::ReqStampNotInReqExt)
+    }
+}
+// endregion: --- ReqStamp Extractor
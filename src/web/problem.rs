@@ -0,0 +1,130 @@
+//! Client error bodies, as our `{"error": {...}}` JSON or as RFC 7807
+//! `application/problem+json` (see `ClientError::problem_type` for the catalog).
+
+use std::str::FromStr;
+
+use axum::{
+    Json,
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use serde_json::{Value, json};
+use tracing::debug;
+use uuid::Uuid;
+
+use crate::config::config;
+use crate::error::ClientError;
+use crate::{Error, Result};
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+// region: --- Error Format
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorFormat {
+    Json,    // {"error": {"type", "req_uuid", "detail"}}
+    Problem, // RFC 7807
+}
+
+impl FromStr for ErrorFormat {
+    type Err = Error;
+
+    fn from_str(format: &str) -> Result<Self> {
+        match format {
+            "json" => Ok(Self::Json),
+            "problem" => Ok(Self::Problem),
+            _ => Err(Error::ConfigWrongFormat("SERVICE_ERROR_FORMAT")),
+        }
+    }
+}
+
+impl ErrorFormat {
+    /// An `Accept` naming one of the formats selects it, the one of higher `q` if both
+    /// (problem on a tie), `q=0` ones excepted. The configured one is the default.
+    pub fn negotiate(headers: &HeaderMap) -> Self {
+        let accept = headers
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        // The highest `q` of the ranges naming the mime, None if none or only `q=0` ones
+        let quality = |mime: &str| {
+            accept
+                .split(',')
+                .filter_map(|range| {
+                    let mut params = range.split(';').map(str::trim);
+                    if params.next() != Some(mime) {
+                        return None;
+                    }
+                    let q = params
+                        .filter_map(|param| param.split_once('='))
+                        .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+                        .map_or(Some(1.0), |(_, q)| q.trim().parse::<f32>().ok())?;
+
+                    Some(q)
+                })
+                .filter(|q| *q > 0.0)
+                .reduce(f32::max)
+        };
+
+        match (quality(PROBLEM_JSON), quality("application/json")) {
+            (Some(problem), Some(json)) if json > problem => Self::Json,
+            (Some(_), _) => Self::Problem,
+            (None, Some(_)) => Self::Json,
+            (None, None) => config().error_format,
+        }
+    }
+}
+// endregion: --- Error Format
+
+/// The client response of a service error, in the given format.
+pub fn client_error_response(
+    format: ErrorFormat,
+    req_uuid: Uuid,
+    status: StatusCode,
+    client_error: &ClientError,
+    service_error: Option<&Error>,
+) -> Response {
+    match format {
+        ErrorFormat::Json => {
+            let mut body = json!({
+                "error": {
+                    "type": client_error.as_ref(),
+                    "req_uuid": req_uuid.to_string(),
+                }
+            });
+            if let Some(detail) = service_error.and_then(|e| e.client_detail()) {
+                body["error"]["detail"] = detail;
+            }
+            debug!("{:<12} - client_error_body: {body}", "RES_MAPPER");
+
+            (status, Json(body)).into_response()
+        }
+
+        ErrorFormat::Problem => {
+            let mut body = json!({
+                "type": client_error.problem_type(),
+                "title": client_error.title(),
+                "status": status.as_u16(),
+                "instance": format!("urn:uuid:{req_uuid}"),
+            });
+            if let Some(detail) = service_error.and_then(|e| e.client_message()) {
+                body["detail"] = Value::String(detail);
+            }
+            // Extension members, never overriding the standard ones
+            if let (Value::Object(body), Some(Value::Object(data))) =
+                (&mut body, service_error.and_then(|e| e.client_data()))
+            {
+                for (name, value) in data {
+                    body.entry(name).or_insert(value);
+                }
+            }
+            debug!("{:<12} - client_error_body: {body}", "RES_MAPPER");
+
+            let mut response = (status, Json(body)).into_response();
+            response
+                .headers_mut()
+                .insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+
+            response
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use crate::{
+    Result,
+    ctx::Permission,
+    log::{RequestLogFilter, RequestLogLine, list_recent_request_logs},
+    web::{extract::ValidQuery, mw_auth::mw_require_permission},
+};
+use axum::{Json, Router, middleware, routing::get};
+use tracing::debug;
+
+pub fn routes() -> Router {
+    Router::new()
+        .route("/admin/request-logs", get(list_request_logs))
+        .route_layer(middleware::from_fn_with_state(
+            Permission::RequestLogRead,
+            mw_require_permission,
+        ))
+}
+
+// region: --- REST Handlers
+#[axum::debug_handler]
+async fn list_request_logs(
+    ValidQuery(filter): ValidQuery<RequestLogFilter>,
+) -> Result<Json<Vec<RequestLogLine>>> {
+    debug!("{:<12} - list_request_logs - {filter:?}", "HANDLER");
+
+    let lines = list_recent_request_logs(&filter)?;
+
+    Ok(Json(lines))
+}
+
+// endregion: --- REST Handlers
@@ -0,0 +1,70 @@
+use crate::{
+    Result,
+    ctx::Ctx,
+    model::{ApiKey, ApiKeyForCreate, ModelController},
+    web::extract::{ValidJson, ValidPath},
+};
+use axum::{
+    Json, Router,
+    extract::State,
+    routing::{delete, post},
+};
+use serde::Serialize;
+use tracing::debug;
+use uuid::Uuid;
+
+pub fn routes(mc: ModelController) -> Router {
+    Router::new()
+        .route("/keys", post(create_api_key).get(list_api_keys))
+        .route("/keys/{id}", delete(delete_api_key))
+        .with_state(mc)
+}
+
+// region: --- REST Handlers
+#[axum::debug_handler]
+async fn create_api_key(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    ValidJson(api_key_fc): ValidJson<ApiKeyForCreate>,
+) -> Result<Json<ApiKeyCreated>> {
+    debug!("{:<12} - create_api_key", "HANDLER");
+
+    let (api_key, key) = mc.create_api_key(ctx, api_key_fc).await?;
+
+    Ok(Json(ApiKeyCreated {
+        key: key.to_string(),
+        api_key,
+    }))
+}
+
+#[axum::debug_handler]
+async fn list_api_keys(State(mc): State<ModelController>, ctx: Ctx) -> Result<Json<Vec<ApiKey>>> {
+    debug!("{:<12} - list_api_keys", "HANDLER");
+
+    let api_keys = mc.list_api_keys(ctx).await?;
+
+    Ok(Json(api_keys))
+}
+
+#[axum::debug_handler]
+async fn delete_api_key(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    ValidPath(id): ValidPath<Uuid>,
+) -> Result<Json<ApiKey>> {
+    debug!("{:<12} - delete_api_key", "HANDLER");
+
+    let api_key = mc.delete_api_key(ctx, id).await?;
+
+    Ok(Json(api_key))
+}
+
+// endregion: --- REST Handlers
+
+/// The clear key is only returned at creation.
+#[derive(Serialize)]
+struct ApiKeyCreated {
+    key: String,
+    #[serde(flatten)]
+    api_key: ApiKey,
+}
@@ -0,0 +1,82 @@
+use crate::{
+    Result,
+    ctx::Ctx,
+    model::{Comment, CommentForCreate, CommentForUpdate, ModelController},
+    web::extract::{ValidJson, ValidPath},
+};
+use axum::{
+    Json, Router,
+    extract::State,
+    routing::{get, patch},
+};
+use tracing::debug;
+
+pub fn routes(mc: ModelController) -> Router {
+    Router::new()
+        .route(
+            "/tickets/{id}/comments",
+            get(list_comments).post(create_comment),
+        )
+        .route(
+            "/tickets/{id}/comments/{comment_id}",
+            patch(update_comment).delete(delete_comment),
+        )
+        .with_state(mc)
+}
+
+// region: --- REST Handlers
+#[axum::debug_handler]
+async fn create_comment(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    ValidPath(ticket_id): ValidPath<u64>,
+    ValidJson(comment_fc): ValidJson<CommentForCreate>,
+) -> Result<Json<Comment>> {
+    debug!("{:<12} - create_comment", "HANDLER");
+
+    let comment = mc.create_comment(ctx, ticket_id, comment_fc).await?;
+
+    Ok(Json(comment))
+}
+
+#[axum::debug_handler]
+async fn list_comments(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    ValidPath(ticket_id): ValidPath<u64>,
+) -> Result<Json<Vec<Comment>>> {
+    debug!("{:<12} - list_comments", "HANDLER");
+
+    let comments = mc.list_comments(ctx, ticket_id).await?;
+
+    Ok(Json(comments))
+}
+
+#[axum::debug_handler]
+async fn update_comment(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    ValidPath((ticket_id, id)): ValidPath<(u64, u64)>,
+    ValidJson(comment_fu): ValidJson<CommentForUpdate>,
+) -> Result<Json<Comment>> {
+    debug!("{:<12} - update_comment", "HANDLER");
+
+    let comment = mc.update_comment(ctx, ticket_id, id, comment_fu).await?;
+
+    Ok(Json(comment))
+}
+
+#[axum::debug_handler]
+async fn delete_comment(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    ValidPath((ticket_id, id)): ValidPath<(u64, u64)>,
+) -> Result<Json<Comment>> {
+    debug!("{:<12} - delete_comment", "HANDLER");
+
+    let comment = mc.delete_comment(ctx, ticket_id, id).await?;
+
+    Ok(Json(comment))
+}
+
+// endregion: --- REST Handlers
@@ -1,24 +1,60 @@
-use axum::{Json, Router, routing::post};
+use axum::{Json, Router, extract::State, http::HeaderMap, routing::post};
+use lazy_regex::{Lazy, Regex, lazy_regex};
 use serde::Deserialize;
 use serde_json::{Value, json};
-use tower_cookies::{Cookie, Cookies};
+use tower_cookies::Cookies;
+use tracing::debug;
+use validator::{Validate, ValidationError};
 
+use crate::crypt::pwd::validate_pwd;
+use crate::crypt::token::{Token, generate_web_token, validate_web_token};
+use crate::ctx::{Ctx, Permission, Role};
+use crate::error::FieldError;
+use crate::model::{ModelController, UserForCreate, check_in_scopes};
+use crate::web::extract::ValidJson;
+use crate::web::mw_auth::{AuthCredential, auth_credential};
 use crate::{Error, Result, web};
 
-pub fn routes() -> Router {
-    Router::new().route("/api/login", post(api_login))
+static USERNAME_RE: Lazy<Regex> = lazy_regex!(r#"^[A-Za-z0-9_.-]*$"#);
+
+/// Verified against on unknown usernames, so they take as long as the known ones
+/// (same Argon2 parameters as `hash_pwd`).
+const DUMMY_PWD_HASH: &str = "$argon2id$v=19$m=19456,t=2,p=1$ZEImxFD8DJ42K/6REoling$Tt1GMYJHfvrDna791NAqGpZpT13YkagtdGAeiPj4PC4";
+
+pub fn routes(mc: ModelController) -> Router {
+    Router::new()
+        .route("/api/login", post(api_login))
+        .route("/api/register", post(api_register))
+        .route("/api/logout", post(api_logout))
+        .route("/api/logout/all", post(api_logout_all))
+        .with_state(mc)
 }
 
-async fn api_login(cookies: Cookies, payload: Json<LoginPayload>) -> Result<Json<Value>> {
-    println!("->> {:<12} - api_login", "HANDLER");
+async fn api_login(
+    State(mc): State<ModelController>,
+    cookies: Cookies,
+    ValidJson(payload): ValidJson<LoginPayload>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_login", "HANDLER");
 
-    // TODO: Implement real db/auth logic
-    if payload.username != "admin" || payload.pwd != "admin" {
-        return Err(Error::LoginFail);
-    }
+    let LoginPayload { username, pwd } = payload;
 
-    // FIXME: Implement real auth-token generation/signature
-    cookies.add(Cookie::new(web::AUTH_TOKEN, "user-1.exp.sign"));
+    // -- Get the user
+    let Some(user) = mc.first_user_by_username(&username).await? else {
+        // Not to disclose the existing usernames by the response time
+        let _ = validate_pwd(pwd, DUMMY_PWD_HASH.to_string()).await;
+        return Err(Error::LoginFailUsernameNotFound);
+    };
+    let user_id = user.id;
+
+    // -- Validate the password
+    validate_pwd(pwd, user.pwd)
+        .await
+        .map_err(|_| Error::LoginFailPwdNotMatching { user_id })?;
+
+    // -- Set the web token
+    let token = generate_web_token(user_id, &user.token_salt.to_string())?;
+    web::set_token_cookie(&cookies, &token);
 
     // Create the success body.
     let body = Json(json!({
@@ -30,8 +66,139 @@ async fn api_login(cookies: Cookies, payload: Json<LoginPayload>) -> Result<Json
     Ok(body)
 }
 
-#[derive(Debug, Deserialize)]
+async fn api_register(
+    State(mc): State<ModelController>,
+    cookies: Cookies,
+    ValidJson(payload): ValidJson<RegisterPayload>,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_register", "HANDLER");
+
+    let RegisterPayload {
+        username,
+        pwd,
+        login,
+    } = payload;
+
+    // The payload rules are field by field, this one spans both
+    if pwd.eq_ignore_ascii_case(&username) {
+        return Err(Error::RegisterFailValidation {
+            errors: vec![FieldError {
+                field: "pwd".into(),
+                message: "must not be the username".to_string(),
+            }],
+        });
+    }
+
+    let user = mc
+        .create_user(UserForCreate {
+            username,
+            pwd_clear: pwd,
+            roles: vec![Role::Reporter],
+        })
+        .await?;
+
+    // -- Optionally log the new user in
+    if login {
+        let token = generate_web_token(user.id, &user.token_salt.to_string())?;
+        web::set_token_cookie(&cookies, &token);
+    }
+
+    let body = Json(json!({
+        "result": {
+            "success": true,
+            "user": user,
+        }
+    }));
+
+    Ok(body)
+}
+
+/// Password strength, beyond its length.
+fn validate_pwd_strength(pwd: &str) -> core::result::Result<(), ValidationError> {
+    if !pwd.chars().any(char::is_alphabetic) || !pwd.chars().any(|c| c.is_ascii_digit()) {
+        let mut error = ValidationError::new("pwd_strength");
+        error.message = Some("must contain at least one letter and one digit".into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+/// Log out the current session, of the same credential as for the other routes
+/// (`Authorization: Bearer` token or cookie).
+/// The cookie is removed, and the session revoked if the token is still valid.
+async fn api_logout(
+    State(mc): State<ModelController>,
+    cookies: Cookies,
+    headers: HeaderMap,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_logout", "HANDLER");
+
+    // API keys are not sessions, they are revoked with `DELETE /api/keys/:id`
+    let token = match auth_credential(&headers, &cookies) {
+        Ok(AuthCredential::CookieToken(token) | AuthCredential::BearerToken(token)) => {
+            token.parse::<Token>().ok()
+        }
+        _ => None,
+    };
+
+    if let Some(token) = token {
+        let salt = mc.user_token_salt(token.user_id).await.ok();
+        let is_valid = salt.is_some_and(|s| validate_web_token(&token, &s.to_string()).is_ok());
+
+        // Only valid tokens are worth revoking (the others are rejected anyway)
+        if is_valid {
+            mc.revoke_session(token.user_id, token.origin).await?;
+        }
+    }
+
+    web::remove_token_cookie(&cookies);
+
+    Ok(logout_body())
+}
+
+/// Log out all the sessions of the current user.
+async fn api_logout_all(
+    State(mc): State<ModelController>,
+    cookies: Cookies,
+    ctx: Ctx,
+) -> Result<Json<Value>> {
+    debug!("{:<12} - api_logout_all", "HANDLER");
+
+    check_in_scopes(&ctx, Permission::CredentialManage)?;
+    mc.reset_user_token_salt(ctx.user_id()).await?;
+
+    web::remove_token_cookie(&cookies);
+
+    Ok(logout_body())
+}
+
+fn logout_body() -> Json<Value> {
+    Json(json!({
+        "result": {
+            "logged_out": true
+        }
+    }))
+}
+
+#[derive(Debug, Deserialize, Validate)]
 struct LoginPayload {
     username: String,
     pwd: String,
 }
+
+#[derive(Debug, Deserialize, Validate)]
+struct RegisterPayload {
+    #[validate(
+        length(min = 3, max = 32),
+        regex(
+            path = *USERNAME_RE,
+            message = "must only contain letters, digits, '_', '.' or '-'"
+        )
+    )]
+    username: String,
+    #[validate(length(min = 8), custom(function = "validate_pwd_strength"))]
+    pwd: String,
+    #[serde(default)]
+    login: bool, // also log the new user in
+}
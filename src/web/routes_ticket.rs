@@ -1,18 +1,43 @@
 use crate::{
     Result,
-    ctx::Ctx,
-    model::{ModelController, Ticket, TicketForCreate},
+    ctx::{Ctx, Permission},
+    model::{
+        HistoryEntry, ModelController, Ticket, TicketFilter, TicketForCreate, TicketForTransition,
+        TicketForUpdate, TicketListOptions, TicketPage,
+    },
+    web::{
+        extract::{ValidJson, ValidPath, ValidQuery},
+        mw_auth::mw_require_permission,
+    },
 };
 use axum::{
     Json, Router,
-    extract::{Path, State},
-    routing::{delete, post},
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
 };
+use tracing::debug;
 
 pub fn routes(mc: ModelController) -> Router {
+    let routes_purge = Router::new()
+        .route("/tickets/{id}/purge", delete(purge_ticket))
+        .route_layer(middleware::from_fn_with_state(
+            Permission::TicketPurge,
+            mw_require_permission,
+        ));
+
     Router::new()
         .route("/tickets", post(create_ticket).get(list_tickets))
-        .route("/tickets/{id}", delete(delete_ticket))
+        .route(
+            "/tickets/{id}",
+            get(get_ticket).patch(update_ticket).delete(delete_ticket),
+        )
+        .route("/tickets/{id}/transition", post(transition_ticket))
+        .route("/tickets/{id}/history", get(list_ticket_history))
+        .route("/tickets/{id}/restore", post(restore_ticket))
+        .merge(routes_purge)
         .with_state(mc)
 }
 
@@ -21,9 +46,9 @@ pub fn routes(mc: ModelController) -> Router {
 async fn create_ticket(
     State(mc): State<ModelController>,
     ctx: Ctx,
-    Json(ticket_fc): Json<TicketForCreate>,
+    ValidJson(ticket_fc): ValidJson<TicketForCreate>,
 ) -> Result<Json<Ticket>> {
-    println!("->> {:<12} - create_ticket", "HANDLER");
+    debug!("{:<12} - create_ticket", "HANDLER");
 
     let ticket = mc.create_ticket(ctx, ticket_fc).await?;
 
@@ -31,23 +56,167 @@ async fn create_ticket(
 }
 
 #[axum::debug_handler]
-async fn list_tickets(State(mc): State<ModelController>, ctx: Ctx) -> Result<Json<Vec<Ticket>>> {
-    println!("->> {:<12} - list_tickets", "HANDLER");
-    let tickets = mc.list_tickets(ctx).await?;
-    Ok(Json(tickets))
+async fn list_tickets(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    ValidQuery(filter): ValidQuery<TicketFilter>,
+    ValidQuery(options): ValidQuery<TicketListOptions>,
+) -> Result<Json<TicketPage>> {
+    debug!("{:<12} - list_tickets - {filter:?} {options:?}", "HANDLER");
+    let page = mc.list_tickets(ctx, filter, options).await?;
+    Ok(Json(page))
+}
+
+#[axum::debug_handler]
+async fn get_ticket(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    ValidPath(id): ValidPath<u64>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    debug!("{:<12} - get_ticket", "HANDLER");
+
+    let ticket = mc.get_ticket(ctx, id).await?;
+
+    // The client copy is still current
+    if if_none_match(&headers, &ticket) {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag(&ticket))]).into_response());
+    }
+
+    Ok(ticket_response(ticket))
+}
+
+#[axum::debug_handler]
+async fn update_ticket(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    ValidPath(id): ValidPath<u64>,
+    headers: HeaderMap,
+    ValidJson(ticket_fu): ValidJson<TicketForUpdate>,
+) -> Result<Response> {
+    debug!("{:<12} - update_ticket", "HANDLER");
+
+    let ticket = mc
+        .update_ticket(ctx, id, ticket_fu, if_match(&headers))
+        .await?;
+
+    Ok(ticket_response(ticket))
+}
+
+#[axum::debug_handler]
+async fn transition_ticket(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    ValidPath(id): ValidPath<u64>,
+    headers: HeaderMap,
+    ValidJson(ticket_ft): ValidJson<TicketForTransition>,
+) -> Result<Response> {
+    debug!("{:<12} - transition_ticket", "HANDLER");
+
+    let ticket = mc
+        .transition_ticket(ctx, id, ticket_ft, if_match(&headers))
+        .await?;
+
+    Ok(ticket_response(ticket))
+}
+
+#[axum::debug_handler]
+async fn list_ticket_history(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    ValidPath(id): ValidPath<u64>,
+) -> Result<Json<Vec<HistoryEntry>>> {
+    debug!("{:<12} - list_ticket_history", "HANDLER");
+
+    let history = mc.list_ticket_history(ctx, id).await?;
+
+    Ok(Json(history))
 }
 
 #[axum::debug_handler]
 async fn delete_ticket(
     State(mc): State<ModelController>,
     ctx: Ctx,
-    Path(id): Path<u64>,
+    ValidPath(id): ValidPath<u64>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    debug!("{:<12} - delete_ticket", "HANDLER");
+
+    let ticket = mc.delete_ticket(ctx, id, if_match(&headers)).await?;
+
+    Ok(ticket_response(ticket))
+}
+
+#[axum::debug_handler]
+async fn restore_ticket(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    ValidPath(id): ValidPath<u64>,
+) -> Result<Response> {
+    debug!("{:<12} - restore_ticket", "HANDLER");
+
+    let ticket = mc.restore_ticket(ctx, id).await?;
+
+    Ok(ticket_response(ticket))
+}
+
+#[axum::debug_handler]
+async fn purge_ticket(
+    State(mc): State<ModelController>,
+    ctx: Ctx,
+    ValidPath(id): ValidPath<u64>,
 ) -> Result<Json<Ticket>> {
-    println!(">>> {:<15} - delete_ticket", "HANDLER");
+    debug!("{:<12} - purge_ticket", "HANDLER");
 
-    let ticket = mc.delete_ticket(ctx, id).await?;
+    let ticket = mc.purge_ticket(ctx, id).await?;
 
     Ok(Json(ticket))
 }
 
 // endregion: --- REST Handlers
+
+// region: --- Conditional Requests
+/// Strong entity tag of the ticket version, e.g., `"3"`.
+fn etag(ticket: &Ticket) -> String {
+    format!("\"{}\"", ticket.version)
+}
+
+fn ticket_response(ticket: Ticket) -> Response {
+    ([(header::ETAG, etag(&ticket))], Json(ticket)).into_response()
+}
+
+/// The versions the client expects the ticket to be at (any of them),
+/// None without `If-Match` (or with `*`).
+/// The tags that are not ours are skipped, as the weak ones (`If-Match` compares strongly),
+/// so that a list of none of ours never matches.
+fn if_match(headers: &HeaderMap) -> Option<Vec<u64>> {
+    let value = headers.get(header::IF_MATCH)?.to_str().unwrap_or_default();
+    let tags: Vec<&str> = value.split(',').map(str::trim).collect();
+    if tags.contains(&"*") {
+        return None;
+    }
+
+    let versions = tags
+        .iter()
+        .filter_map(|tag| tag.strip_prefix('"')?.strip_suffix('"')?.parse().ok())
+        .collect();
+
+    Some(versions)
+}
+
+/// Whether one of the `If-None-Match` tags (weak or not) is the current ticket one.
+fn if_none_match(headers: &HeaderMap, ticket: &Ticket) -> bool {
+    let Some(value) = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return false;
+    };
+
+    let current = etag(ticket);
+    value
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == current)
+}
+// endregion: --- Conditional Requests
@@ -0,0 +1,44 @@
+use crate::{
+    Result,
+    ctx::{Permission, Role},
+    model::{ModelController, User},
+    web::{
+        extract::{ValidJson, ValidPath},
+        mw_auth::mw_require_permission,
+    },
+};
+use axum::{Json, Router, extract::State, middleware, routing::put};
+use serde::Deserialize;
+use tracing::debug;
+use validator::Validate;
+
+pub fn routes(mc: ModelController) -> Router {
+    Router::new()
+        .route("/users/{id}/roles", put(update_user_roles))
+        .route_layer(middleware::from_fn_with_state(
+            Permission::UserManage,
+            mw_require_permission,
+        ))
+        .with_state(mc)
+}
+
+// region: --- REST Handlers
+#[axum::debug_handler]
+async fn update_user_roles(
+    State(mc): State<ModelController>,
+    ValidPath(id): ValidPath<u64>,
+    ValidJson(payload): ValidJson<UserRolesPayload>,
+) -> Result<Json<User>> {
+    debug!("{:<12} - update_user_roles", "HANDLER");
+
+    let user = mc.update_user_roles(id, payload.roles).await?;
+
+    Ok(Json(user))
+}
+
+// endregion: --- REST Handlers
+
+#[derive(Debug, Deserialize, Validate)]
+struct UserRolesPayload {
+    roles: Vec<Role>,
+}
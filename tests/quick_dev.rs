@@ -8,6 +8,16 @@ async fn quick_dev() -> Result<()> {
     hc.do_get("/hello?name=Person1").await?.print().await?; // No cookie yet
     // hc.do_get("/src/main.rs").await?.print().await?;
 
+    // USERNAME_TAKEN when run again against the same server
+    let req_register = hc.do_post(
+        "/api/register",
+        json!({
+            "username": "demo1",
+            "pwd": "welcome1"
+        }),
+    );
+    req_register.await?.print().await?;
+
     // Cookie is set here
     let req_login = hc.do_post(
         "/api/login",
@@ -20,17 +30,55 @@ async fn quick_dev() -> Result<()> {
 
     // hc.do_get("/hello2/Person2").await?.print().await?; // Cookie exists
 
+    // Admin only (Permission::UserManage)
+    let req_update_roles = hc.do_put("/api/users/2/roles", json!({ "roles": ["agent"] }));
+    req_update_roles.await?.print().await?;
+
     let req_create_ticket = hc.do_post(
         "/api/tickets",
         json!({
-            "title": "My first ticket"
+            "title": "My first ticket",
+            "priority": "high",
+            "labels": ["bug"]
         }),
     );
     req_create_ticket.await?.print().await?;
+    // hc.do_post("/api/tickets", json!({ "title": "" })).await?.print().await?; // INVALID_PARAMS
 
+    // hc.do_get("/api/tickets/1").await?.print().await?;
+    // hc.do_patch("/api/tickets/1", json!({ "title": "My first ticket (edited)" })).await?.print().await?;
+    // hc.do_post("/api/tickets/1/transition", json!({ "status": "in_progress" })).await?.print().await?;
+    // hc.do_post("/api/tickets/1/comments", json!({ "body": "A first comment" })).await?.print().await?;
+    // hc.do_get("/api/tickets/1/comments").await?.print().await?;
     // hc.do_delete("/api/tickets/1").await?.print().await?;
+    // hc.do_post("/api/tickets/1/restore", json!({})).await?.print().await?;
+    // hc.do_get("/api/tickets/1/history").await?.print().await?;
 
     hc.do_get("/api/tickets").await?.print().await?;
+    // hc.do_get("/api/tickets?mine=true").await?.print().await?;
+    // hc.do_get("/api/tickets?limit=10&sort=ctime&order=desc").await?.print().await?;
+    // hc.do_get("/api/tickets?assignee=me&label=bug&priority=high").await?.print().await?;
+
+    // The clear key is only returned here, use as `Authorization: Bearer key-...`
+    let req_create_api_key = hc.do_post(
+        "/api/keys",
+        json!({
+            "name": "quick_dev",
+            "scopes": ["TicketReadAny"]
+        }),
+    );
+    req_create_api_key.await?.print().await?;
+    // hc.do_get("/api/keys").await?.print().await?;
+
+    // Admin only (others get `NO_PERMISSION`)
+    // hc.do_get("/api/admin/request-logs?user_id=1&path_prefix=/api/tickets").await?.print().await?;
+    // hc.do_get("/api/admin/request-logs?error_type=AuthFailTokenWrongFormat").await?.print().await?;
+
+    // Cookie is removed and the session revoked here
+    hc.do_post("/api/logout", json!({})).await?.print().await?;
+    // hc.do_post("/api/logout/all", json!({})).await?.print().await?;
+
+    hc.do_get("/api/tickets").await?.print().await?; // NO_AUTH
 
     Ok(())
 }